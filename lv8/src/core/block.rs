@@ -2,16 +2,16 @@ use std::{cell::RefCell, rc::Rc};
 
 use super::{
     scope::{self, Scope, ValueType},
-    statement, Metadata,
+    statement, Metadata, PrimitiveTypes,
 };
 use lv8_common::error::Result;
-use lv8_parser::Block as BlockAST;
+use lv8_parser::{Block as BlockAST, StatementKind};
 
 #[derive(Clone, Debug)]
 pub struct Block {
     block: BlockAST,
     pub scope: Rc<RefCell<Scope>>,
-    metadata: Rc<Metadata>,
+    pub metadata: Rc<Metadata>,
 }
 
 impl Block {
@@ -48,4 +48,28 @@ impl Block {
 
         self.return_type()
     }
+
+    /// Runs what was typed at the REPL and returns the value to show, the one of its last
+    /// statement when that is an expression or a call so `1 + 2` and `Math.sqrt(2)` show
+    /// their result. `undefined` is not shown.
+    pub fn call_interactive(self) -> Result<Option<ValueType>> {
+        let mut value = None;
+
+        for statement in &self.block.0 {
+            let result = statement::run_statement(&self.scope, statement, &self.metadata)?;
+
+            value = matches!(
+                statement.kind,
+                StatementKind::Expression(_) | StatementKind::FunctionCall { .. }
+            )
+            .then_some(result);
+        }
+
+        let value = match self.return_type()? {
+            ValueType::Variable(PrimitiveTypes::Undefined) => value,
+            returned => Some(returned),
+        };
+
+        Ok(value.filter(|value| !matches!(value, ValueType::Variable(PrimitiveTypes::Undefined))))
+    }
 }
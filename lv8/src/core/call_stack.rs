@@ -0,0 +1,45 @@
+use std::cell::RefCell;
+
+use lv8_common::{
+    error::{ErrorKind, Frame, Result},
+    source::Span,
+};
+
+/// The functions, modules and files being evaluated, shared by every file of a program
+/// so errors raised in an import show where the import was called from.
+#[derive(Debug, Default)]
+pub struct CallStack {
+    frames: RefCell<Vec<Frame>>,
+}
+
+impl CallStack {
+    /// Runs `body` in a new frame. The first frame an error leaves records the whole stack
+    /// as the traceback of the error.
+    pub fn enter<T>(&self, name: &str, body: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.frames.borrow_mut().push(Frame::new(name));
+
+        let result = body().map_err(|mut error| {
+            if error.traceback.is_empty() && !matches!(error.kind, ErrorKind::Exit(_)) {
+                error.traceback = self.frames.borrow().clone();
+
+                // The span of the error is more precise than the statement being run.
+                if let (Some(span), Some(frame)) = (error.span, error.traceback.last_mut()) {
+                    frame.span = Some(span);
+                }
+            }
+
+            error
+        });
+
+        self.frames.borrow_mut().pop();
+
+        result
+    }
+
+    /// Records the statement the current frame is running.
+    pub fn set_position(&self, span: Span) {
+        if let Some(frame) = self.frames.borrow_mut().last_mut() {
+            frame.span = Some(span);
+        }
+    }
+}
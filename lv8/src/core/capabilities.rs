@@ -0,0 +1,140 @@
+use std::{
+    env,
+    path::{Component, Path, PathBuf},
+};
+
+/// Set of resources a script is allowed to touch.
+///
+/// Standard library functions that need a capability are only installed when it is
+/// granted, and path based operations (imports, filesystem access) are checked against
+/// the allowed roots.
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    pub stdin: bool,
+    pub stdout: bool,
+    pub env: bool,
+    pub process: bool,
+    pub fs_read: PathAccess,
+    pub fs_write: PathAccess,
+    pub imports: PathAccess,
+}
+
+impl Capabilities {
+    /// Everything is allowed, this is what the CLI uses unless sandboxed.
+    pub fn all() -> Self {
+        Self {
+            stdin: true,
+            stdout: true,
+            env: true,
+            process: true,
+            fs_read: PathAccess::Any,
+            fs_write: PathAccess::Any,
+            imports: PathAccess::Any,
+        }
+    }
+
+    /// Nothing is allowed, the script can only compute values.
+    pub fn none() -> Self {
+        Self {
+            stdin: false,
+            stdout: false,
+            env: false,
+            process: false,
+            fs_read: PathAccess::Denied,
+            fs_write: PathAccess::Denied,
+            imports: PathAccess::Denied,
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PathAccess {
+    Denied,
+    /// Normalized absolute roots, build it with `PathAccess::within`.
+    Within(Vec<PathBuf>),
+    Any,
+}
+
+impl PathAccess {
+    /// Resolves `roots` against the current directory once, so the sandbox doesn't move
+    /// when the script changes directory. Roots that can't be resolved allow nothing.
+    pub fn within<I>(roots: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        PathAccess::Within(
+            roots
+                .into_iter()
+                .filter_map(|root| normalize(root.as_ref()))
+                .collect(),
+        )
+    }
+
+    pub fn is_denied(&self) -> bool {
+        matches!(self, PathAccess::Denied)
+    }
+
+    pub fn allows<P>(&self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        match self {
+            PathAccess::Denied => false,
+            PathAccess::Any => true,
+            PathAccess::Within(roots) => {
+                let Some(path) = normalize(path.as_ref()) else {
+                    return false;
+                };
+
+                roots.iter().any(|root| path.starts_with(root))
+            }
+        }
+    }
+}
+
+/// `path` joined to the current directory when it is relative, nothing is resolved.
+pub fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().unwrap_or_default().join(path)
+    }
+}
+
+/// Makes `path` absolute and resolves `.`, `..` and symlinks so that a path cannot
+/// escape its root with tricks like `root/../etc/passwd` or `root/link/../secret`.
+///
+/// Components are resolved in order like the OS does, a symlink is followed before the
+/// `..` after it applies. Components that don't exist yet (e.g. a file about to be
+/// written) are kept as they are. `None` when the path goes through a symlink that can't
+/// be resolved, a dangling one or a loop.
+pub fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut resolved = PathBuf::new();
+
+    for component in absolute(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => {
+                resolved.push(component);
+
+                match resolved.canonicalize() {
+                    Ok(canonical) => resolved = canonical,
+                    Err(_) if resolved.is_symlink() => return None,
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+
+    Some(resolved)
+}
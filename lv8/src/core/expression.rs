@@ -6,9 +6,10 @@ mod math_expression;
 mod namespace_expression;
 
 use lv8_common::error::Result;
-use lv8_parser::Expression as ExpressionAST;
+use lv8_parser::{Expression as ExpressionAST, ExpressionKind};
 
 pub use logic_expression::value_to_bool;
+pub use namespace_expression::evaluate_method;
 
 use super::{
     scope::{evaluate_expression, Scope, ValueType},
@@ -19,13 +20,13 @@ pub struct Expression;
 
 impl Expression {
     pub fn parse_expression(scope: &Rc<RefCell<Scope>>, ast: ExpressionAST) -> Result<ValueType> {
-        let result = match ast {
-            ExpressionAST::Null => ValueType::Variable(PrimitiveTypes::Null),
-            ExpressionAST::Undefined => ValueType::Variable(PrimitiveTypes::Undefined),
-            ExpressionAST::Boolean(value) => ValueType::Variable(PrimitiveTypes::Boolean(value)),
-            ExpressionAST::Number(value) => ValueType::Variable(PrimitiveTypes::Number(value)),
-            ExpressionAST::String(value) => ValueType::Variable(PrimitiveTypes::String(value)),
-            ExpressionAST::Array(value) => {
+        let result = match ast.kind {
+            ExpressionKind::Null => ValueType::Variable(PrimitiveTypes::Null),
+            ExpressionKind::Undefined => ValueType::Variable(PrimitiveTypes::Undefined),
+            ExpressionKind::Boolean(value) => ValueType::Variable(PrimitiveTypes::Boolean(value)),
+            ExpressionKind::Number(value) => ValueType::Variable(PrimitiveTypes::Number(value)),
+            ExpressionKind::String(value) => ValueType::Variable(PrimitiveTypes::String(value)),
+            ExpressionKind::Array(value) => {
                 let mut array = Vec::new();
 
                 for element in value {
@@ -34,7 +35,7 @@ impl Expression {
 
                 ValueType::Variable(PrimitiveTypes::Array(array))
             }
-            ExpressionAST::Object(value) => {
+            ExpressionKind::Object(value) => {
                 let mut object = BTreeMap::new();
 
                 for (key, value) in value {
@@ -43,18 +44,17 @@ impl Expression {
 
                 ValueType::Variable(PrimitiveTypes::Object(object))
             }
-            // ExpressionAST::Identifier(value) => evaluate_expression(scope, &ast),
-            ExpressionAST::Identifier(value) => ValueType::Variable(PrimitiveTypes::String(value)),
-            ExpressionAST::Namespace(value) => {
+            ExpressionKind::Identifier(value) => ValueType::Variable(PrimitiveTypes::String(value)),
+            ExpressionKind::Namespace(value) => {
                 namespace_expression::evaluate_namespace_expression(scope, value)?
             }
-            ExpressionAST::MathExpression(value) => {
+            ExpressionKind::MathExpression(value) => {
                 ValueType::Variable(math_expression::evaluate_math_expression(scope, value)?)
             }
-            ExpressionAST::LogicExpression(value) => ValueType::Variable(PrimitiveTypes::Boolean(
+            ExpressionKind::LogicExpression(value) => ValueType::Variable(PrimitiveTypes::Boolean(
                 logic_expression::evaluate_logic_expression(scope, value)?,
             )),
-            ExpressionAST::ComparisonExpression(value) => {
+            ExpressionKind::ComparisonExpression(value) => {
                 ValueType::Variable(PrimitiveTypes::Boolean(
                     comparison_expression::evaluate_comparison_expression(scope, value)?,
                 ))
@@ -72,6 +72,7 @@ fn primitive_types_to_bool(value: PrimitiveTypes) -> bool {
         PrimitiveTypes::Boolean(value) => value,
         PrimitiveTypes::Array(_) => true,
         PrimitiveTypes::Object(_) => true,
+        PrimitiveTypes::DateTime(_) => true,
         PrimitiveTypes::Null => false,
         PrimitiveTypes::Undefined => false,
         PrimitiveTypes::Number(number) => match number {
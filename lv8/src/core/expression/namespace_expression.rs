@@ -1,8 +1,8 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::core::{
-    scope::{Scope, ValueType},
-    PrimitiveTypes,
+    scope::{self, Scope, ValueType},
+    stdlib, PrimitiveTypes,
 };
 use lv8_common::error::{Error, Result};
 
@@ -13,10 +13,7 @@ pub fn evaluate_namespace_expression(
     let mut value = scope.borrow().get(&namespace[0]);
 
     if value.is_none() {
-        return Err(Error::r#type(&format!(
-            "cannot read properties of {:#?}",
-            namespace[0]
-        )));
+        return Err(scope::not_defined(scope, &namespace[0]));
     }
 
     for ident in &namespace[1..] {
@@ -26,6 +23,29 @@ pub fn evaluate_namespace_expression(
     Ok(value.unwrap_or(ValueType::Variable(PrimitiveTypes::Undefined)))
 }
 
+/// Resolves the callee of `receiver.name(...)`.
+///
+/// When the receiver has no `name` property the standard library methods of its type are
+/// used instead, in that case the receiver is returned so it can be passed as the first
+/// argument.
+pub fn evaluate_method(
+    scope: &Rc<RefCell<Scope>>,
+    namespace: &[String],
+) -> Result<(ValueType, Option<ValueType>)> {
+    let (name, receiver) = namespace.split_last().unwrap();
+    let receiver = evaluate_namespace_expression(scope, receiver.to_vec())?;
+
+    match read_property_of_value(Some(receiver.clone()), name)? {
+        None | Some(ValueType::Variable(PrimitiveTypes::Undefined)) => {
+            match stdlib::method(&receiver, name) {
+                Some(method) => Ok((method, Some(receiver))),
+                None => Ok((ValueType::Variable(PrimitiveTypes::Undefined), None)),
+            }
+        }
+        Some(property) => Ok((property, None)),
+    }
+}
+
 fn read_property_of_value(value: Option<ValueType>, ident: &str) -> Result<Option<ValueType>> {
     if value.is_none() {
         return Err(Error::r#type(&format!(
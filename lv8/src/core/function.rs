@@ -1,5 +1,7 @@
 use lv8_common::error::{Error, Result};
-use lv8_parser::{Block as BlockAST, Either, Expression as ExpressionAST, Statement};
+use lv8_parser::{
+    Block as BlockAST, Either, Expression as ExpressionAST, ExpressionKind, Statement,
+};
 use std::{
     cell::RefCell,
     fmt::{self, Debug},
@@ -8,6 +10,7 @@ use std::{
 
 use super::{
     block::Block,
+    expression,
     scope::{self, Scope, ValueType},
     Metadata, PrimitiveTypes,
 };
@@ -28,6 +31,14 @@ impl Function {
         }
     }
 
+    pub fn arity(&self) -> usize {
+        self.expected_parameters.len()
+    }
+
+    pub fn parameters(&self) -> &[String] {
+        &self.expected_parameters
+    }
+
     pub fn call(mut self, parameters: Vec<ValueType>) -> Result<ValueType> {
         let scope = &mut self.body.scope;
 
@@ -41,7 +52,51 @@ impl Function {
             scope.borrow_mut().set(expected_parameter, parameter);
         }
 
-        self.body.call()
+        let call_stack = Rc::clone(&self.body.metadata.call_stack);
+
+        call_stack.enter(&self.name, || self.body.call())
+    }
+}
+
+pub type NativeFunction = fn(Vec<ValueType>) -> Result<ValueType>;
+
+/// Native function that needs the metadata of the evaluator it was installed in, e.g.
+/// to resolve relative paths or check capabilities.
+pub type MetadataFunction = fn(&Metadata, Vec<ValueType>) -> Result<ValueType>;
+
+#[derive(Clone)]
+pub struct InternalFunction {
+    pub name: String,
+    function: Rc<dyn Fn(Vec<ValueType>) -> Result<ValueType>>,
+}
+
+impl InternalFunction {
+    pub fn new(name: &str, function: NativeFunction) -> Self {
+        Self {
+            name: name.to_string(),
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn with_metadata(name: &str, function: MetadataFunction, metadata: &Rc<Metadata>) -> Self {
+        let metadata = Rc::clone(metadata);
+
+        Self::from_closure(name, move |parameters| function(&metadata, parameters))
+    }
+
+    /// For functions that carry their own state, like the handles returned by `Process.spawn`.
+    pub fn from_closure<F>(name: &str, function: F) -> Self
+    where
+        F: Fn(Vec<ValueType>) -> Result<ValueType> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn call(&self, parameters: Vec<ValueType>) -> Result<ValueType> {
+        (self.function)(parameters)
     }
 }
 
@@ -51,9 +106,13 @@ pub fn handle_function_call(
     arguments: &[Either<ExpressionAST, Statement>],
     metadata: &Rc<Metadata>,
 ) -> Result<ValueType> {
-    let function = scope::evaluate_expression(scope, expression)?;
+    let (function, receiver) = match &expression.kind {
+        ExpressionKind::Namespace(namespace) => expression::evaluate_method(scope, namespace)
+            .map_err(|error| error.with_span(expression.span))?,
+        _ => (scope::evaluate_expression(scope, expression)?, None),
+    };
 
-    let mut args = Vec::new();
+    let mut args = Vec::from_iter(receiver);
 
     for argument in arguments {
         let argument = match argument {
@@ -66,9 +125,15 @@ pub fn handle_function_call(
         args.push(argument);
     }
 
+    call_function(function, args)
+}
+
+/// Calls a user defined or internal function value, this is also how standard library
+/// functions like `Array.map` invoke their callbacks.
+pub fn call_function(function: ValueType, args: Vec<ValueType>) -> Result<ValueType> {
     match function {
         ValueType::Function(function) => function.call(args),
-        ValueType::InternalFunction(function) => Ok(function(args)),
+        ValueType::InternalFunction(function) => function.call(args),
         _ => Err(Error::r#type(&format!("{} is not a function", function))),
     }
 }
@@ -118,3 +183,21 @@ impl PartialOrd for Function {
         self.name.partial_cmp(&other.name)
     }
 }
+
+impl fmt::Display for InternalFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<<internal function {}>>", self.name)
+    }
+}
+
+impl PartialEq for InternalFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialOrd for InternalFunction {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.name.partial_cmp(&other.name)
+    }
+}
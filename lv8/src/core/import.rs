@@ -1,15 +1,15 @@
-use std::path;
-use std::{cell::RefCell, rc::Rc};
+use std::path::{self, Path};
+use std::{cell::RefCell, fs, rc::Rc};
 
 use crate::core::PrimitiveTypes;
 
-use super::{execute_file, module};
+use super::{execute_file, module, stdlib};
 use super::{
     scope::{Scope, ValueType},
     Metadata,
 };
 
-use lv8_common::error::Result;
+use lv8_common::error::{Error, Result};
 
 pub fn import_statement(
     scope: &Rc<RefCell<Scope>>,
@@ -19,14 +19,54 @@ pub fn import_statement(
 ) -> Result<ValueType> {
     let path = path::Path::new(&metadata.pw).join(path);
 
-    let evaluator = execute_file(path)?;
+    if !metadata.capabilities.imports.allows(&path) {
+        return Err(Error::permission(&format!(
+            "import of {:?} is not allowed",
+            path
+        )));
+    }
 
-    let module = module::Module {
-        name: ident.to_string(),
-        scope: evaluator.scope,
+    let module = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        import_json(&path, ident)?
+    } else {
+        let evaluator = execute_file(path, metadata)?;
+
+        module::Module {
+            name: ident.to_string(),
+            scope: evaluator.scope,
+        }
     };
 
     scope.borrow_mut().set(ident, ValueType::Module(module));
 
     Ok(ValueType::Variable(PrimitiveTypes::Undefined))
 }
+
+/// The keys of the top level object become the members of the module.
+fn import_json(path: &Path, ident: &str) -> Result<module::Module> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| Error::io(&format!("cannot import {:?}: {}", path, e)))?;
+
+    let entries = match stdlib::json::parse("import", &text)? {
+        ValueType::Variable(PrimitiveTypes::Object(entries)) => entries,
+        value => {
+            return Err(Error::r#type(&format!(
+                "cannot import {:?}: expected a JSON object, got {}",
+                path,
+                value.type_name()
+            )))
+        }
+    };
+
+    let mut scope = Scope::new(ident);
+
+    scope.variables.extend(entries);
+
+    Ok(module::Module {
+        name: ident.to_string(),
+        scope: Rc::new(RefCell::new(scope)),
+    })
+}
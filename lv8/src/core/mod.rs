@@ -1,3 +1,6 @@
+use call_stack::CallStack;
+use capabilities::Capabilities;
+use chrono::{DateTime, FixedOffset, SecondsFormat};
 use lv8_common::error::Result;
 use lv8_parser::{ASTNode, Either};
 use owo_colors::OwoColorize;
@@ -5,12 +8,14 @@ use scope::{Scope, ValueType};
 use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::fmt::{self, Debug};
-use std::path::{self, Path};
+use std::path::Path;
 use std::rc::Rc;
 
 use crate::read;
 
 mod block;
+mod call_stack;
+pub mod capabilities;
 mod expression;
 mod flow_control;
 mod function;
@@ -29,6 +34,7 @@ pub enum PrimitiveTypes {
     String(String),
     Array(Vec<ValueType>),
     Object(BTreeMap<String, ValueType>),
+    DateTime(DateTime<FixedOffset>),
 }
 
 impl fmt::Display for PrimitiveTypes {
@@ -45,6 +51,9 @@ impl fmt::Display for PrimitiveTypes {
             PrimitiveTypes::Object(value) => {
                 write!(f, "{:#?}", value)
             }
+            PrimitiveTypes::DateTime(value) => {
+                write!(f, "{}", value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+            }
         }
     }
 }
@@ -60,6 +69,7 @@ impl Debug for PrimitiveTypes {
             PrimitiveTypes::Undefined => write!(f, "{}", "undefined".bright_black()),
             PrimitiveTypes::Null => write!(f, "{}", "null".bright_black()),
             PrimitiveTypes::Boolean(value) => write!(f, "{}", value.bright_blue()),
+            PrimitiveTypes::DateTime(_) => write!(f, "{}", self.to_string().cyan()),
             _ => write!(f, "{}", self),
         }
     }
@@ -68,6 +78,62 @@ impl Debug for PrimitiveTypes {
 #[derive(Debug)]
 pub struct Metadata {
     pub pw: String,
+    pub capabilities: Rc<Capabilities>,
+    pub argv: Rc<Vec<String>>,
+    pub exit_handlers: Rc<RefCell<Vec<ValueType>>>,
+    pub random: Rc<RefCell<fastrand::Rng>>,
+    pub call_stack: Rc<CallStack>,
+}
+
+impl Metadata {
+    /// Without a seed the random number generator is seeded from the system.
+    pub fn new(
+        pw: String,
+        capabilities: Capabilities,
+        argv: Vec<String>,
+        seed: Option<u64>,
+    ) -> Self {
+        let random = match seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+
+        Self {
+            pw,
+            capabilities: Rc::new(capabilities),
+            argv: Rc::new(argv),
+            exit_handlers: Rc::new(RefCell::new(Vec::new())),
+            random: Rc::new(RefCell::new(random)),
+            call_stack: Rc::new(CallStack::default()),
+        }
+    }
+
+    /// Metadata for another file of the same program, everything but the working
+    /// directory is shared.
+    pub fn with_pw(&self, pw: String) -> Self {
+        Self {
+            pw,
+            capabilities: Rc::clone(&self.capabilities),
+            argv: Rc::clone(&self.argv),
+            exit_handlers: Rc::clone(&self.exit_handlers),
+            random: Rc::clone(&self.random),
+            call_stack: Rc::clone(&self.call_stack),
+        }
+    }
+
+    /// Calls the functions registered with `OS.at_exit`, the last registered runs first.
+    pub fn run_exit_handlers(&self) -> Result<()> {
+        loop {
+            let handler = self.exit_handlers.borrow_mut().pop();
+
+            match handler {
+                Some(handler) => {
+                    function::call_function(handler, vec![])?;
+                }
+                None => return Ok(()),
+            }
+        }
+    }
 }
 
 pub struct Evaluator {
@@ -78,13 +144,14 @@ pub struct Evaluator {
 impl Evaluator {
     pub fn new(metadata: Metadata) -> Self {
         let mut scope = scope::Scope::new("global");
+        let metadata = Rc::new(metadata);
 
-        let standard_library = stdlib::build_standard_library();
+        let standard_library = stdlib::build_standard_library(&metadata);
         scope.extend(standard_library);
 
         Self {
             scope: Rc::new(RefCell::new(scope)),
-            metadata: Rc::new(metadata),
+            metadata,
         }
     }
 
@@ -93,31 +160,98 @@ impl Evaluator {
             ASTNode::Block(block) => {
                 let block = block::Block::new(block, self.scope.clone(), self.metadata.clone());
 
-                block.call()
+                self.metadata.call_stack.enter("<module>", || block.call())
+            }
+        }
+    }
+
+    /// The names visible at the top level, with the standard library.
+    pub fn names(&self) -> Vec<String> {
+        self.scope.borrow().names()
+    }
+
+    /// The members of the module or object at `path`, like `["Math"]` for `Math.`.
+    pub fn members(&self, path: &[&str]) -> Vec<String> {
+        match self.resolve(path) {
+            Some(ValueType::Module(module)) => {
+                module.scope.borrow().variables.keys().cloned().collect()
+            }
+            Some(ValueType::Variable(PrimitiveTypes::Object(entries))) => {
+                entries.keys().cloned().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The parameter names of the function at `path`, `None` for native functions which
+    /// don't name them.
+    pub fn parameters(&self, path: &[&str]) -> Option<Vec<String>> {
+        match self.resolve(path)? {
+            ValueType::Function(function) => Some(function.parameters().to_vec()),
+            _ => None,
+        }
+    }
+
+    /// The value at a path like `Math.pi`, without evaluating anything.
+    fn resolve(&self, path: &[&str]) -> Option<ValueType> {
+        let (first, members) = path.split_first()?;
+        let mut value = self.scope.borrow().get(first)?;
+
+        for member in members {
+            value = match value {
+                ValueType::Module(module) => module.scope.borrow().variables.get(*member)?.clone(),
+                ValueType::Variable(PrimitiveTypes::Object(mut entries)) => {
+                    entries.remove(*member)?
+                }
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+
+    /// Runs input of the REPL, see [`block::Block::call_interactive`].
+    pub fn execute_interactive(&self, ast: ASTNode) -> Result<Option<ValueType>> {
+        match ast {
+            ASTNode::Block(block) => {
+                let block = block::Block::new(block, self.scope.clone(), self.metadata.clone());
+
+                self.metadata
+                    .call_stack
+                    .enter("<module>", || block.call_interactive())
             }
         }
     }
 }
 
-pub fn execute_file<P>(path: P) -> Result<Evaluator>
+pub fn execute_file<P>(path: P, metadata: &Metadata) -> Result<Evaluator>
 where
     P: AsRef<Path>,
 {
     let ast = read::read_file(&path)?;
-    let core = Evaluator::new(Metadata {
-        pw: path::absolute(path)
-            .unwrap()
-            .parent()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_string(),
-    });
-
-    match core.execute(ast) {
-        Ok(_) => {}
-        Err(e) => eprintln!("{:?}", e),
-    }
+    let core = Evaluator::new(
+        metadata.with_pw(
+            capabilities::absolute(path.as_ref())
+                .parent()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string(),
+        ),
+    );
+
+    core.execute(ast)?;
+
+    Ok(core)
+}
+
+/// Runs a script that isn't a file, like `lv8 -e` or a script piped to stdin. Imports
+/// are resolved from the current directory.
+pub fn execute_source(name: &str, input: &str, metadata: &Metadata) -> Result<Evaluator> {
+    let ast = read::parse(name, input)?;
+    let core = Evaluator::new(metadata.with_pw(metadata.pw.clone()));
+
+    core.execute(ast)?;
 
     Ok(core)
 }
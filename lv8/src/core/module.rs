@@ -20,9 +20,13 @@ impl Module {
     ) -> Result<Self> {
         let module_scope = Rc::new(RefCell::new(Scope::with_parent(name, Rc::clone(&scope))));
 
-        for statement in &body.0 {
-            run_statement(&module_scope, statement, metadata)?;
-        }
+        metadata.call_stack.enter(name, || {
+            for statement in &body.0 {
+                run_statement(&module_scope, statement, metadata)?;
+            }
+
+            Ok(())
+        })?;
 
         Ok(Self {
             name: name.to_owned(),
@@ -4,28 +4,50 @@ use std::fmt;
 use std::rc::Rc;
 use std::{collections::HashMap, fmt::Debug};
 
-use lv8_common::error::{Error, Result};
-use lv8_parser::Expression as ExpressionAST;
+use lv8_common::error::{suggest, Error, Result};
+use lv8_parser::{Expression as ExpressionAST, ExpressionKind};
 
 use super::module::Module;
-use super::{expression::Expression, function::Function, PrimitiveTypes};
+use super::{
+    expression::Expression,
+    function::{Function, InternalFunction},
+    PrimitiveTypes,
+};
 
 #[derive(Clone, PartialEq, PartialOrd)]
 pub enum ValueType {
     Function(Function),
     Variable(PrimitiveTypes),
-    InternalFunction(fn(Vec<ValueType>) -> ValueType),
+    InternalFunction(InternalFunction),
     Module(Module),
 }
 
+impl ValueType {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ValueType::Function(_) => "function",
+            ValueType::InternalFunction(_) => "internal_function",
+            ValueType::Module(_) => "module",
+            ValueType::Variable(value) => match value {
+                PrimitiveTypes::Null => "null",
+                PrimitiveTypes::Undefined => "undefined",
+                PrimitiveTypes::Boolean(_) => "boolean",
+                PrimitiveTypes::Number(_) => "number",
+                PrimitiveTypes::String(_) => "string",
+                PrimitiveTypes::Array(_) => "array",
+                PrimitiveTypes::Object(_) => "object",
+                PrimitiveTypes::DateTime(_) => "datetime",
+            },
+        }
+    }
+}
+
 impl fmt::Display for ValueType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ValueType::Function(function) => write!(f, "{}", function),
             ValueType::Variable(value) => write!(f, "{}", value),
-            ValueType::InternalFunction(func) => {
-                write!(f, "<<internal function {:?}>>", &func as *const _)
-            }
+            ValueType::InternalFunction(function) => write!(f, "{}", function),
             ValueType::Module(module) => {
                 write!(f, "<<module {}>>", module.name)
             }
@@ -38,12 +60,8 @@ impl Debug for ValueType {
         match self {
             ValueType::Function(function) => write!(f, "{:?}", function.bright_magenta()),
             ValueType::Variable(value) => write!(f, "{:?}", value),
-            ValueType::InternalFunction(func) => {
-                write!(
-                    f,
-                    "{}",
-                    format!("<<internal function {:?}>>", &func as *const _).bright_magenta()
-                )
+            ValueType::InternalFunction(function) => {
+                write!(f, "{}", function.to_string().bright_magenta())
             }
             ValueType::Module(module) => {
                 write!(
@@ -107,6 +125,17 @@ impl Scope {
         }
     }
 
+    /// The names visible from this scope, including the ones of its parents.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.variables.keys().cloned().collect();
+
+        if let Some(parent) = &self.parent {
+            names.extend(parent.borrow().names());
+        }
+
+        names
+    }
+
     pub fn extend(&mut self, variables: HashMap<String, ValueType>) {
         self.variables.extend(variables);
     }
@@ -116,18 +145,24 @@ pub fn evaluate_expression(
     scope: &Rc<RefCell<Scope>>,
     expression: &ExpressionAST,
 ) -> Result<ValueType> {
-    let value = match expression {
-        ExpressionAST::Identifier(ref identifier) => scope.borrow().get(identifier),
-        _ => Some(Expression::parse_expression(scope, expression.clone())?),
-    };
-
-    if let Some(value) = value {
-        Ok(value)
-    } else {
-        Err(Error::reference(&format!(
-            "{:?} variable not found",
-            expression
-        )))
+    match &expression.kind {
+        ExpressionKind::Identifier(identifier) => scope
+            .borrow()
+            .get(identifier)
+            .ok_or_else(|| not_defined(scope, identifier).with_span(expression.span)),
+        _ => Expression::parse_expression(scope, expression.clone())
+            .map_err(|error| error.with_span(expression.span)),
+    }
+}
+
+/// A `ReferenceError` suggesting the closest name of the scope chain.
+pub fn not_defined(scope: &Rc<RefCell<Scope>>, name: &str) -> Error {
+    let names = scope.borrow().names();
+    let error = Error::reference(&format!("{} is not defined", name)).with_label("not defined");
+
+    match suggest(name, names.iter().map(String::as_str)) {
+        Some(suggestion) => error.with_help(&format!("did you mean `{}`?", suggestion)),
+        None => error,
     }
 }
 
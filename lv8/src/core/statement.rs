@@ -1,7 +1,7 @@
 use std::{cell::RefCell, rc::Rc};
 
 use lv8_common::error::Result;
-use lv8_parser::{Either, Statement as StatementAST};
+use lv8_parser::{Either, Statement as StatementAST, StatementKind};
 
 use super::{
     flow_control, function, import,
@@ -15,8 +15,18 @@ pub fn run_statement(
     statement: &StatementAST,
     metadata: &Rc<Metadata>,
 ) -> Result<ValueType> {
-    match statement {
-        StatementAST::Assignment { left, right } => {
+    metadata.call_stack.set_position(statement.span);
+
+    run(scope, statement, metadata).map_err(|error| error.with_span(statement.span))
+}
+
+fn run(
+    scope: &Rc<RefCell<Scope>>,
+    statement: &StatementAST,
+    metadata: &Rc<Metadata>,
+) -> Result<ValueType> {
+    match &statement.kind {
+        StatementKind::Assignment { left, right } => {
             let value = match right {
                 Either::Left(expression) => scope::evaluate_expression(scope, expression)?,
                 Either::Right(statement) => run_statement(scope, statement, metadata)?,
@@ -28,18 +38,18 @@ pub fn run_statement(
 
             Ok(value)
         }
-        StatementAST::FunctionDefinition {
+        StatementKind::FunctionDefinition {
             name,
             parameters,
             body,
         } => Ok(function::handle_function_definition(
             scope, name, parameters, body, metadata,
         )),
-        StatementAST::FunctionCall {
+        StatementKind::FunctionCall {
             expression,
             arguments,
         } => function::handle_function_call(scope, expression, arguments, metadata),
-        StatementAST::If {
+        StatementKind::If {
             condition,
             body,
             else_if,
@@ -51,18 +61,19 @@ pub fn run_statement(
             else_body,
             metadata,
         )?),
-        StatementAST::While { condition, body } => Ok(flow_control::while_statement(
+        StatementKind::While { condition, body } => Ok(flow_control::while_statement(
             scope, condition, body, metadata,
         )?),
-        StatementAST::ModuleDefinition { name, body } => {
+        StatementKind::ModuleDefinition { name, body } => {
             let module = Module::new(name, Rc::clone(scope), body, metadata)?;
 
             scope.borrow_mut().set(name, ValueType::Module(module));
 
             Ok(ValueType::Variable(super::PrimitiveTypes::Undefined))
         }
-        StatementAST::Import { path, ident } => {
+        StatementKind::Import { path, ident } => {
             Ok(import::import_statement(scope, path, ident, metadata)?)
         }
+        StatementKind::Expression(expression) => scope::evaluate_expression(scope, expression),
     }
 }
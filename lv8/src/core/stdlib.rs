@@ -1,19 +1,346 @@
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 
-use super::{scope::ValueType, PrimitiveTypes};
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
 
-pub fn build_standard_library() -> HashMap<String, ValueType> {
+use super::{
+    function::{InternalFunction, MetadataFunction, NativeFunction},
+    module::Module,
+    scope::{Scope, ValueType},
+    Metadata, PrimitiveTypes,
+};
+
+mod array;
+mod csv;
+mod format;
+mod fs;
+pub mod json;
+mod math;
+mod object;
+mod os;
+mod process;
+mod random;
+mod regex;
+mod string;
+mod time;
+mod types;
+
+/// Builds the global functions and modules, leaving out everything the
+/// capabilities of the evaluator don't grant.
+pub fn build_standard_library(metadata: &Rc<Metadata>) -> HashMap<String, ValueType> {
+    let capabilities = &metadata.capabilities;
     let mut standard_library = HashMap::new();
 
-    standard_library.insert("printl".to_string(), ValueType::InternalFunction(printl));
-    standard_library.insert("print".to_string(), ValueType::InternalFunction(print));
-    standard_library.insert("input".to_string(), ValueType::InternalFunction(input));
-    standard_library.insert("inspect".to_string(), ValueType::InternalFunction(inspect));
+    if capabilities.stdout {
+        insert_function(&mut standard_library, "printl", printl);
+        insert_function(&mut standard_library, "print", print);
+        insert_function(&mut standard_library, "inspect", inspect);
+        insert_function(&mut standard_library, "printf", format::printf);
+    }
+
+    if capabilities.stdin {
+        insert_function(&mut standard_library, "input", input);
+    }
+
+    insert_function(&mut standard_library, "format", format::format);
+
+    for (name, function) in types::FUNCTIONS {
+        insert_function(&mut standard_library, name, *function);
+    }
+
+    standard_library.insert(
+        "argv".to_string(),
+        array(metadata.argv.iter().cloned().map(string).collect()),
+    );
+
+    standard_library.insert("Math".to_string(), math::build_module());
+    standard_library.insert(
+        "String".to_string(),
+        build_module("String", string::FUNCTIONS, vec![]),
+    );
+    standard_library.insert(
+        "Array".to_string(),
+        build_module("Array", array::FUNCTIONS, vec![]),
+    );
+    standard_library.insert(
+        "Object".to_string(),
+        build_module("Object", object::FUNCTIONS, vec![]),
+    );
+
+    standard_library.insert(
+        "Random".to_string(),
+        build_metadata_module("Random", random::FUNCTIONS, metadata),
+    );
+    standard_library.insert(
+        "Time".to_string(),
+        build_module("Time", time::FUNCTIONS, vec![]),
+    );
+    standard_library.insert(
+        "Regex".to_string(),
+        build_module("Regex", regex::FUNCTIONS, vec![]),
+    );
+    standard_library.insert(
+        "JSON".to_string(),
+        build_module("JSON", json::FUNCTIONS, vec![]),
+    );
+
+    standard_library.insert(
+        "CSV".to_string(),
+        build_metadata_module("CSV", csv::FUNCTIONS, metadata),
+    );
+
+    if !capabilities.fs_read.is_denied() || !capabilities.fs_write.is_denied() {
+        standard_library.insert(
+            "FS".to_string(),
+            build_metadata_module("FS", fs::FUNCTIONS, metadata),
+        );
+    }
+
+    if capabilities.process {
+        standard_library.insert(
+            "Process".to_string(),
+            build_metadata_module("Process", process::FUNCTIONS, metadata),
+        );
+    }
+
+    let mut os_functions = os::FUNCTIONS.to_vec();
+
+    if capabilities.env {
+        os_functions.extend_from_slice(os::ENV_FUNCTIONS);
+    }
+
+    standard_library.insert(
+        "OS".to_string(),
+        build_metadata_module("OS", &os_functions, metadata),
+    );
 
     standard_library
 }
 
-fn printl(args: Vec<ValueType>) -> ValueType {
+fn insert_function(library: &mut HashMap<String, ValueType>, name: &str, function: NativeFunction) {
+    library.insert(
+        name.to_string(),
+        ValueType::InternalFunction(InternalFunction::new(name, function)),
+    );
+}
+
+/// Looks up the standard library function backing `value.name(...)` calls on
+/// primitive values, e.g. `name.upper()` is `String.upper(name)`.
+pub fn method(value: &ValueType, name: &str) -> Option<ValueType> {
+    let (module, functions) = match value {
+        ValueType::Variable(PrimitiveTypes::String(_)) => ("String", string::FUNCTIONS),
+        ValueType::Variable(PrimitiveTypes::Array(_)) => ("Array", array::FUNCTIONS),
+        ValueType::Variable(PrimitiveTypes::Object(_)) => ("Object", object::FUNCTIONS),
+        ValueType::Variable(PrimitiveTypes::DateTime(_)) => ("Time", time::METHODS),
+        _ => return None,
+    };
+
+    functions
+        .iter()
+        .find(|(function_name, _)| *function_name == name)
+        .map(|(function_name, function)| {
+            ValueType::InternalFunction(InternalFunction::new(
+                &format!("{}.{}", module, function_name),
+                *function,
+            ))
+        })
+}
+
+/// Wraps native functions and constants into a module value, function names are
+/// prefixed with the module name so errors and `inspect` show `Math.sqrt`.
+fn build_module<'a>(
+    name: &str,
+    functions: &[(&'a str, NativeFunction)],
+    mut members: Vec<(&'a str, ValueType)>,
+) -> ValueType {
+    for (function_name, function) in functions {
+        members.push((
+            function_name,
+            ValueType::InternalFunction(InternalFunction::new(
+                &format!("{}.{}", name, function_name),
+                *function,
+            )),
+        ));
+    }
+
+    module_value(name, members)
+}
+
+/// Like `build_module`, for functions bound to the metadata of the evaluator.
+fn build_metadata_module(
+    name: &str,
+    functions: &[(&str, MetadataFunction)],
+    metadata: &Rc<Metadata>,
+) -> ValueType {
+    let members = functions
+        .iter()
+        .map(|(function_name, function)| {
+            (
+                *function_name,
+                ValueType::InternalFunction(InternalFunction::with_metadata(
+                    &format!("{}.{}", name, function_name),
+                    *function,
+                    metadata,
+                )),
+            )
+        })
+        .collect();
+
+    module_value(name, members)
+}
+
+fn module_value(name: &str, members: Vec<(&str, ValueType)>) -> ValueType {
+    let mut scope = Scope::new(name);
+
+    for (member_name, value) in members {
+        scope.variables.insert(member_name.to_string(), value);
+    }
+
+    ValueType::Module(Module {
+        name: name.to_string(),
+        scope: Rc::new(RefCell::new(scope)),
+    })
+}
+
+fn null() -> ValueType {
+    ValueType::Variable(PrimitiveTypes::Null)
+}
+
+fn undefined() -> ValueType {
+    ValueType::Variable(PrimitiveTypes::Undefined)
+}
+
+fn boolean(value: bool) -> ValueType {
+    ValueType::Variable(PrimitiveTypes::Boolean(value))
+}
+
+fn number(value: Either<isize, f64>) -> ValueType {
+    ValueType::Variable(PrimitiveTypes::Number(value))
+}
+
+fn integer(value: usize) -> ValueType {
+    number(Either::Left(value as isize))
+}
+
+fn string(value: String) -> ValueType {
+    ValueType::Variable(PrimitiveTypes::String(value))
+}
+
+fn array(values: Vec<ValueType>) -> ValueType {
+    ValueType::Variable(PrimitiveTypes::Array(values))
+}
+
+fn object(entries: BTreeMap<String, ValueType>) -> ValueType {
+    ValueType::Variable(PrimitiveTypes::Object(entries))
+}
+
+fn as_f64(value: &Either<isize, f64>) -> f64 {
+    match value {
+        Either::Left(value) => *value as f64,
+        Either::Right(value) => *value,
+    }
+}
+
+/// Turns a possibly negative index into an offset clamped to `0..=len`, negative
+/// indexes count from the end like in Python.
+fn clamp_index(index: isize, len: usize) -> usize {
+    if index < 0 {
+        len.saturating_sub(index.unsigned_abs())
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+/// The longest string, in bytes, the standard library builds out of a count
+/// given by a script, larger requests fail instead of exhausting memory.
+const MAX_STRING_LENGTH: usize = 1 << 26;
+
+fn too_long_error(function: &str) -> Error {
+    Error::value(&format!(
+        "{}() result would be longer than {} bytes",
+        function, MAX_STRING_LENGTH
+    ))
+}
+
+fn type_error(function: &str, expected: &str, args: &[ValueType], index: usize) -> Error {
+    Error::r#type(&format!(
+        "{}() expects {} at position {}, got {}",
+        function,
+        expected,
+        index + 1,
+        args.get(index).map_or("nothing", ValueType::type_name)
+    ))
+}
+
+fn argument<'a>(function: &str, args: &'a [ValueType], index: usize) -> Result<&'a ValueType> {
+    args.get(index).ok_or_else(|| {
+        Error::r#type(&format!(
+            "{}() missing argument at position {}",
+            function,
+            index + 1
+        ))
+    })
+}
+
+fn number_argument(function: &str, args: &[ValueType], index: usize) -> Result<Either<isize, f64>> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::Number(value)) => Ok(value.clone()),
+        _ => Err(type_error(function, "a number", args, index)),
+    }
+}
+
+fn integer_argument(function: &str, args: &[ValueType], index: usize) -> Result<isize> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::Number(Either::Left(value))) => Ok(*value),
+        _ => Err(type_error(function, "an integer", args, index)),
+    }
+}
+
+fn string_argument<'a>(function: &str, args: &'a [ValueType], index: usize) -> Result<&'a str> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::String(value)) => Ok(value),
+        _ => Err(type_error(function, "a string", args, index)),
+    }
+}
+
+fn array_argument<'a>(
+    function: &str,
+    args: &'a [ValueType],
+    index: usize,
+) -> Result<&'a Vec<ValueType>> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::Array(value)) => Ok(value),
+        _ => Err(type_error(function, "an array", args, index)),
+    }
+}
+
+fn object_argument<'a>(
+    function: &str,
+    args: &'a [ValueType],
+    index: usize,
+) -> Result<&'a BTreeMap<String, ValueType>> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::Object(value)) => Ok(value),
+        _ => Err(type_error(function, "an object", args, index)),
+    }
+}
+
+fn function_argument<'a>(
+    function: &str,
+    args: &'a [ValueType],
+    index: usize,
+) -> Result<&'a ValueType> {
+    match argument(function, args, index)? {
+        value @ (ValueType::Function(_) | ValueType::InternalFunction(_)) => Ok(value),
+        _ => Err(type_error(function, "a function", args, index)),
+    }
+}
+
+fn printl(args: Vec<ValueType>) -> Result<ValueType> {
     let args = args
         .into_iter()
         .map(|x| x.to_string())
@@ -22,10 +349,10 @@ fn printl(args: Vec<ValueType>) -> ValueType {
 
     println!("{}", args);
 
-    ValueType::Variable(PrimitiveTypes::Undefined)
+    Ok(undefined())
 }
 
-fn print(args: Vec<ValueType>) -> ValueType {
+fn print(args: Vec<ValueType>) -> Result<ValueType> {
     let args = args
         .into_iter()
         .map(|x| x.to_string())
@@ -34,10 +361,10 @@ fn print(args: Vec<ValueType>) -> ValueType {
 
     print!("{}", args);
 
-    ValueType::Variable(PrimitiveTypes::Undefined)
+    Ok(undefined())
 }
 
-fn inspect(args: Vec<ValueType>) -> ValueType {
+fn inspect(args: Vec<ValueType>) -> Result<ValueType> {
     let args = args
         .into_iter()
         .map(|x| format!("{:?}", x))
@@ -46,12 +373,14 @@ fn inspect(args: Vec<ValueType>) -> ValueType {
 
     println!("{}", args);
 
-    ValueType::Variable(PrimitiveTypes::Undefined)
+    Ok(undefined())
 }
 
-fn input(_args: Vec<ValueType>) -> ValueType {
+fn input(_args: Vec<ValueType>) -> Result<ValueType> {
     let mut input = String::new();
     std::io::stdin().read_line(&mut input).unwrap();
 
-    ValueType::Variable(PrimitiveTypes::String(input.trim().to_string()))
+    Ok(ValueType::Variable(PrimitiveTypes::String(
+        input.trim().to_string(),
+    )))
 }
@@ -0,0 +1,520 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{
+    argument, array, array_argument, as_f64, boolean, clamp_index, function_argument, integer,
+    integer_argument, number, object, undefined,
+};
+use crate::core::{
+    expression::value_to_bool,
+    function::{call_function, NativeFunction},
+    scope::ValueType,
+    PrimitiveTypes,
+};
+
+/// Also used for method calls on array values, the array is passed as the first argument.
+///
+/// Arrays are values, so no function modifies its argument, `push`, `insert` and `remove`
+/// return the updated array instead, and `pop` returns `[rest, element]`.
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[
+    ("len", len),
+    ("get", get),
+    ("push", push),
+    ("pop", pop),
+    ("insert", insert),
+    ("remove", remove),
+    ("concat", concat),
+    ("slice", slice),
+    ("index_of", index_of),
+    ("includes", includes),
+    ("reverse", reverse),
+    ("sort", sort),
+    ("map", map),
+    ("filter", filter),
+    ("reduce", reduce),
+    ("each", each),
+    ("any", any),
+    ("all", all),
+    ("zip", zip),
+    ("enumerate", enumerate),
+    ("flatten", flatten),
+    ("unique", unique),
+    ("group_by", group_by),
+    ("join", super::string::join),
+];
+
+/// Resolves a possibly negative index to a position inside an array of `len` elements.
+fn element_index(index: isize, len: usize) -> Option<usize> {
+    if index < 0 {
+        len.checked_sub(index.unsigned_abs())
+    } else if (index as usize) < len {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
+
+fn out_of_range(function: &str, index: isize, len: usize) -> Error {
+    Error::r#type(&format!(
+        "{}() index {} is out of range for an array of length {}",
+        function, index, len
+    ))
+}
+
+/// Natural ordering used by `Array.sort`, numbers are compared by value regardless of
+/// being integers or floats.
+fn compare_values(left: &ValueType, right: &ValueType) -> Result<Ordering> {
+    let ordering = match (left, right) {
+        (
+            ValueType::Variable(PrimitiveTypes::Number(left)),
+            ValueType::Variable(PrimitiveTypes::Number(right)),
+        ) => match (left, right) {
+            (Either::Left(left), Either::Left(right)) => Some(left.cmp(right)),
+            _ => as_f64(left).partial_cmp(&as_f64(right)),
+        },
+        (
+            ValueType::Variable(PrimitiveTypes::String(left)),
+            ValueType::Variable(PrimitiveTypes::String(right)),
+        ) => Some(left.cmp(right)),
+        (
+            ValueType::Variable(PrimitiveTypes::Boolean(left)),
+            ValueType::Variable(PrimitiveTypes::Boolean(right)),
+        ) => Some(left.cmp(right)),
+        (
+            ValueType::Variable(PrimitiveTypes::DateTime(left)),
+            ValueType::Variable(PrimitiveTypes::DateTime(right)),
+        ) => Some(left.cmp(right)),
+        (
+            ValueType::Variable(PrimitiveTypes::Array(left)),
+            ValueType::Variable(PrimitiveTypes::Array(right)),
+        ) => {
+            for (left, right) in left.iter().zip(right) {
+                match compare_values(left, right)? {
+                    Ordering::Equal => continue,
+                    ordering => return Ok(ordering),
+                }
+            }
+
+            Some(left.len().cmp(&right.len()))
+        }
+        _ => None,
+    };
+
+    ordering.ok_or_else(|| {
+        Error::r#type(&format!(
+            "cannot compare {} with {}",
+            left.type_name(),
+            right.type_name()
+        ))
+    })
+}
+
+fn len(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.len", &args, 0)?;
+
+    Ok(integer(values.len()))
+}
+
+/// `Array.get(array, index, default)`, negative indexes count from the end.
+fn get(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.get", &args, 0)?;
+    let index = integer_argument("Array.get", &args, 1)?;
+
+    match element_index(index, values.len()) {
+        Some(index) => Ok(values[index].clone()),
+        None => Ok(args.get(2).cloned().unwrap_or_else(undefined)),
+    }
+}
+
+fn push(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = array_argument("Array.push", &args, 0)?.clone();
+
+    values.extend(args[1..].iter().cloned());
+
+    Ok(array(values))
+}
+
+/// `Array.pop(array)` returns `[rest, element]`, the element is `undefined` when the
+/// array is empty.
+fn pop(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = array_argument("Array.pop", &args, 0)?.clone();
+    let element = values.pop().unwrap_or_else(undefined);
+
+    Ok(array(vec![array(values), element]))
+}
+
+fn insert(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = array_argument("Array.insert", &args, 0)?.clone();
+    let index = clamp_index(integer_argument("Array.insert", &args, 1)?, values.len());
+    let value = argument("Array.insert", &args, 2)?;
+
+    values.insert(index, value.clone());
+
+    Ok(array(values))
+}
+
+fn remove(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = array_argument("Array.remove", &args, 0)?.clone();
+    let index = integer_argument("Array.remove", &args, 1)?;
+
+    match element_index(index, values.len()) {
+        Some(index) => {
+            values.remove(index);
+
+            Ok(array(values))
+        }
+        None => Err(out_of_range("Array.remove", index, values.len())),
+    }
+}
+
+fn concat(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = Vec::new();
+
+    for index in 0..args.len() {
+        values.extend(
+            array_argument("Array.concat", &args, index)?
+                .iter()
+                .cloned(),
+        );
+    }
+
+    Ok(array(values))
+}
+
+/// `Array.slice(array, start, end)`, `end` defaults to the length of the array.
+fn slice(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.slice", &args, 0)?;
+
+    let start = clamp_index(integer_argument("Array.slice", &args, 1)?, values.len());
+    let end = if args.len() > 2 {
+        clamp_index(integer_argument("Array.slice", &args, 2)?, values.len())
+    } else {
+        values.len()
+    };
+
+    if start >= end {
+        return Ok(array(Vec::new()));
+    }
+
+    Ok(array(values[start..end].to_vec()))
+}
+
+/// Returns the index of the first element equal to the value or `-1`.
+fn index_of(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.index_of", &args, 0)?;
+    let value = argument("Array.index_of", &args, 1)?;
+
+    match values.iter().position(|element| element == value) {
+        Some(index) => Ok(integer(index)),
+        None => Ok(number(Either::Left(-1))),
+    }
+}
+
+fn includes(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.includes", &args, 0)?;
+    let value = argument("Array.includes", &args, 1)?;
+
+    Ok(boolean(values.contains(value)))
+}
+
+fn reverse(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = array_argument("Array.reverse", &args, 0)?.clone();
+
+    values.reverse();
+
+    Ok(array(values))
+}
+
+/// Sorts by the natural ordering, or by the optional function.
+///
+/// A function taking two parameters is used as comparator and must return a number
+/// (negative, zero or positive) or `true` when the first argument goes first. Any other
+/// function is used as key function. Equal values keep their order.
+fn sort(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.sort", &args, 0)?.clone();
+
+    let function = if args.len() > 1 {
+        Some(function_argument("Array.sort", &args, 1)?.clone())
+    } else {
+        None
+    };
+
+    let (comparator, key) = match function {
+        Some(ValueType::Function(function)) if function.arity() == 2 => {
+            (Some(ValueType::Function(function)), None)
+        }
+        key => (None, key),
+    };
+
+    let entries = values
+        .into_iter()
+        .map(|value| match &key {
+            Some(key) => Ok((call_function(key.clone(), vec![value.clone()])?, value)),
+            None => Ok((value.clone(), value)),
+        })
+        .collect::<Result<Vec<(ValueType, ValueType)>>>()?;
+
+    let entries = merge_sort(entries, &mut |(left, _), (right, _)| match &comparator {
+        Some(comparator) => compare_with(comparator, left, right),
+        None => compare_values(left, right),
+    })?;
+
+    Ok(array(entries.into_iter().map(|(_, value)| value).collect()))
+}
+
+/// Stable merge sort stopped by the first error of `compare`. Unlike `sort_by`, it
+/// can't panic when a script gives an inconsistent comparator.
+fn merge_sort<T>(
+    mut values: Vec<T>,
+    compare: &mut impl FnMut(&T, &T) -> Result<Ordering>,
+) -> Result<Vec<T>> {
+    if values.len() < 2 {
+        return Ok(values);
+    }
+
+    let right = values.split_off(values.len() / 2);
+    let mut left = merge_sort(values, compare)?.into_iter().peekable();
+    let mut right = merge_sort(right, compare)?.into_iter().peekable();
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+
+    while let (Some(first), Some(second)) = (left.peek(), right.peek()) {
+        // Equal values keep their order, the left one goes first.
+        let next = if compare(second, first)? == Ordering::Less {
+            right.next()
+        } else {
+            left.next()
+        };
+
+        merged.extend(next);
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+/// Calls a sort comparator. A boolean result only says whether `left` goes first, so
+/// the comparator is called the other way around to tell equal values apart.
+fn compare_with(comparator: &ValueType, left: &ValueType, right: &ValueType) -> Result<Ordering> {
+    let call = |left: &ValueType, right: &ValueType| {
+        call_function(comparator.clone(), vec![left.clone(), right.clone()])
+    };
+
+    match call(left, right)? {
+        ValueType::Variable(PrimitiveTypes::Number(value)) => {
+            Ok(as_f64(&value).partial_cmp(&0.0).unwrap_or(Ordering::Equal))
+        }
+        ValueType::Variable(PrimitiveTypes::Boolean(true)) => Ok(Ordering::Less),
+        ValueType::Variable(PrimitiveTypes::Boolean(false)) => match call(right, left)? {
+            ValueType::Variable(PrimitiveTypes::Boolean(true)) => Ok(Ordering::Greater),
+            ValueType::Variable(PrimitiveTypes::Boolean(false)) => Ok(Ordering::Equal),
+            value => Err(comparator_error(&value)),
+        },
+        value => Err(comparator_error(&value)),
+    }
+}
+
+fn comparator_error(value: &ValueType) -> Error {
+    Error::r#type(&format!(
+        "Array.sort() comparator must return a number or boolean, got {}",
+        value.type_name()
+    ))
+}
+
+fn map(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.map", &args, 0)?;
+    let function = function_argument("Array.map", &args, 1)?;
+
+    let values = values
+        .iter()
+        .map(|value| call_function(function.clone(), vec![value.clone()]))
+        .collect::<Result<Vec<ValueType>>>()?;
+
+    Ok(array(values))
+}
+
+fn filter(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.filter", &args, 0)?;
+    let function = function_argument("Array.filter", &args, 1)?;
+
+    let mut filtered = Vec::new();
+
+    for value in values {
+        if value_to_bool(call_function(function.clone(), vec![value.clone()])?) {
+            filtered.push(value.clone());
+        }
+    }
+
+    Ok(array(filtered))
+}
+
+/// `Array.reduce(array, function, initial)`, without an initial value the first element
+/// is used.
+fn reduce(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.reduce", &args, 0)?;
+    let function = function_argument("Array.reduce", &args, 1)?;
+
+    let mut values = values.iter().cloned();
+
+    let mut accumulator = match args.get(2) {
+        Some(initial) => initial.clone(),
+        None => values.next().ok_or_else(|| {
+            Error::r#type("Array.reduce() of an empty array with no initial value")
+        })?,
+    };
+
+    for value in values {
+        accumulator = call_function(function.clone(), vec![accumulator, value])?;
+    }
+
+    Ok(accumulator)
+}
+
+fn each(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.each", &args, 0)?;
+    let function = function_argument("Array.each", &args, 1)?;
+
+    for value in values {
+        call_function(function.clone(), vec![value.clone()])?;
+    }
+
+    Ok(undefined())
+}
+
+/// Tests every element with the optional function, or by its own truthiness.
+fn test_elements(function: &str, args: &[ValueType]) -> Result<Vec<bool>> {
+    let values = array_argument(function, args, 0)?;
+
+    let predicate = if args.len() > 1 {
+        Some(function_argument(function, args, 1)?)
+    } else {
+        None
+    };
+
+    values
+        .iter()
+        .map(|value| match predicate {
+            Some(predicate) => Ok(value_to_bool(call_function(
+                predicate.clone(),
+                vec![value.clone()],
+            )?)),
+            None => Ok(value_to_bool(value.clone())),
+        })
+        .collect()
+}
+
+fn any(args: Vec<ValueType>) -> Result<ValueType> {
+    let results = test_elements("Array.any", &args)?;
+
+    Ok(boolean(results.into_iter().any(|result| result)))
+}
+
+fn all(args: Vec<ValueType>) -> Result<ValueType> {
+    let results = test_elements("Array.all", &args)?;
+
+    Ok(boolean(results.into_iter().all(|result| result)))
+}
+
+/// Pairs up elements of every array, stopping at the shortest one.
+fn zip(args: Vec<ValueType>) -> Result<ValueType> {
+    let arrays = (0..args.len())
+        .map(|index| array_argument("Array.zip", &args, index))
+        .collect::<Result<Vec<&Vec<ValueType>>>>()?;
+
+    let len = arrays.iter().map(|values| values.len()).min().unwrap_or(0);
+
+    Ok(array(
+        (0..len)
+            .map(|index| array(arrays.iter().map(|values| values[index].clone()).collect()))
+            .collect(),
+    ))
+}
+
+fn enumerate(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.enumerate", &args, 0)?;
+
+    Ok(array(
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| array(vec![integer(index), value.clone()]))
+            .collect(),
+    ))
+}
+
+/// `Array.flatten(array, depth)`, flattens one level unless a depth is given.
+fn flatten(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.flatten", &args, 0)?;
+    let depth = if args.len() > 1 {
+        integer_argument("Array.flatten", &args, 1)?
+    } else {
+        1
+    };
+
+    fn flatten_into(values: &[ValueType], depth: isize, flattened: &mut Vec<ValueType>) {
+        for value in values {
+            match value {
+                ValueType::Variable(PrimitiveTypes::Array(inner)) if depth > 0 => {
+                    flatten_into(inner, depth - 1, flattened)
+                }
+                value => flattened.push(value.clone()),
+            }
+        }
+    }
+
+    let mut flattened = Vec::new();
+    flatten_into(values, depth, &mut flattened);
+
+    Ok(array(flattened))
+}
+
+/// Removes duplicated elements, keeping the first occurrence of each.
+fn unique(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.unique", &args, 0)?;
+
+    let mut unique: Vec<ValueType> = Vec::new();
+
+    for value in values {
+        if !unique.contains(value) {
+            unique.push(value.clone());
+        }
+    }
+
+    Ok(array(unique))
+}
+
+/// Groups elements into an object keyed by the string form of the function result.
+fn group_by(args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Array.group_by", &args, 0)?;
+    let function = function_argument("Array.group_by", &args, 1)?;
+
+    let mut groups: BTreeMap<String, Vec<ValueType>> = BTreeMap::new();
+
+    for value in values {
+        let key = call_function(function.clone(), vec![value.clone()])?;
+
+        let key = match key {
+            ValueType::Variable(PrimitiveTypes::String(key)) => key,
+            ValueType::Variable(_) => key.to_string(),
+            _ => {
+                return Err(Error::r#type(&format!(
+                    "Array.group_by() keys must be values, got {}",
+                    key.type_name()
+                )))
+            }
+        };
+
+        groups.entry(key).or_default().push(value.clone());
+    }
+
+    Ok(object(
+        groups
+            .into_iter()
+            .map(|(key, values)| (key, array(values)))
+            .collect(),
+    ))
+}
@@ -0,0 +1,403 @@
+use std::{cell::RefCell, collections::BTreeMap, fs::File, io, rc::Rc};
+
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{
+    array, array_argument, fs, function_argument, null, number, object, object_argument, string,
+    string_argument, undefined,
+};
+use crate::core::{
+    function::{call_function, InternalFunction, MetadataFunction},
+    scope::ValueType,
+    Metadata, PrimitiveTypes,
+};
+
+/// Fields are always read as strings. In header mode the first row names the fields
+/// and rows are returned as objects, otherwise rows are arrays.
+pub const FUNCTIONS: &[(&str, MetadataFunction)] = &[
+    ("parse", parse),
+    ("stringify", stringify),
+    ("read", read),
+    ("write", write),
+    ("each", each),
+    ("reader", reader),
+];
+
+/// `{delimiter, quote, escape, header, columns}`, the character options take a string
+/// of a single ASCII character.
+struct Options {
+    delimiter: u8,
+    quote: u8,
+    escape: Option<u8>,
+    header: Option<bool>,
+    columns: Option<Vec<String>>,
+}
+
+impl Options {
+    fn parse(function: &str, args: &[ValueType], index: usize) -> Result<Self> {
+        let mut options = Self {
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            header: None,
+            columns: None,
+        };
+
+        if args.len() <= index {
+            return Ok(options);
+        }
+
+        for (key, value) in object_argument(function, args, index)? {
+            match (key.as_str(), value) {
+                ("delimiter", value) => options.delimiter = character(function, key, value)?,
+                ("quote", value) => options.quote = character(function, key, value)?,
+                ("escape", ValueType::Variable(PrimitiveTypes::Null)) => options.escape = None,
+                ("escape", value) => options.escape = Some(character(function, key, value)?),
+                ("header", ValueType::Variable(PrimitiveTypes::Boolean(header))) => {
+                    options.header = Some(*header);
+                }
+                ("columns", ValueType::Variable(PrimitiveTypes::Array(columns))) => {
+                    options.columns = Some(
+                        (0..columns.len())
+                            .map(|index| {
+                                string_argument(function, columns, index).map(str::to_string)
+                            })
+                            .collect::<Result<_>>()?,
+                    );
+                }
+                ("header", value) => return Err(option_error(function, key, "a boolean", value)),
+                ("columns", value) => return Err(option_error(function, key, "an array", value)),
+                _ => {
+                    return Err(Error::value(&format!(
+                        "{}() unknown option {:?}",
+                        function, key
+                    )))
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn reader<R: io::Read>(&self, source: R) -> csv::Reader<R> {
+        ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.escape.is_none())
+            .from_reader(source)
+    }
+
+    fn writer<W: io::Write>(&self, destination: W) -> csv::Writer<W> {
+        let mut builder = WriterBuilder::new();
+
+        builder
+            .flexible(true)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .double_quote(self.escape.is_none());
+
+        if let Some(escape) = self.escape {
+            builder.escape(escape);
+        }
+
+        builder.from_writer(destination)
+    }
+}
+
+fn character(function: &str, option: &str, value: &ValueType) -> Result<u8> {
+    match value {
+        ValueType::Variable(PrimitiveTypes::String(value))
+            if value.len() == 1 && value.is_ascii() =>
+        {
+            Ok(value.as_bytes()[0])
+        }
+        value => Err(option_error(
+            function,
+            option,
+            "a single ASCII character",
+            value,
+        )),
+    }
+}
+
+fn option_error(function: &str, option: &str, expected: &str, value: &ValueType) -> Error {
+    Error::r#type(&format!(
+        "{}() expects {} for option {:?}, got {}",
+        function,
+        expected,
+        option,
+        value.type_name()
+    ))
+}
+
+fn csv_error(function: &str, error: csv::Error) -> Error {
+    if error.is_io_error() {
+        Error::io(&format!("{}() {}", function, error))
+    } else {
+        Error::value(&format!("{}() invalid CSV: {}", function, error))
+    }
+}
+
+/// Turns records into row values. With `header: true` the first row names the fields,
+/// `columns` names them explicitly, and the row becomes an object.
+struct Rows<R: io::Read> {
+    records: csv::StringRecordsIntoIter<R>,
+    header: Option<Vec<String>>,
+}
+
+impl<R: io::Read> Rows<R> {
+    fn new(function: &str, reader: csv::Reader<R>, options: &Options) -> Result<Self> {
+        let mut records = reader.into_records();
+
+        let first = match options.header {
+            Some(true) => Some(
+                records
+                    .next()
+                    .transpose()
+                    .map_err(|e| csv_error(function, e))?
+                    .map(|record| record.iter().map(str::to_string).collect())
+                    .unwrap_or_default(),
+            ),
+            _ => None,
+        };
+
+        let header = options.columns.clone().or(first);
+
+        Ok(Self { records, header })
+    }
+
+    fn next(&mut self, function: &str) -> Result<Option<ValueType>> {
+        match self.records.next() {
+            Some(record) => Ok(Some(self.row(&record.map_err(|e| csv_error(function, e))?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Missing fields of short rows are `null`, extra fields are dropped.
+    fn row(&self, record: &StringRecord) -> ValueType {
+        match &self.header {
+            Some(header) => object(
+                header
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| {
+                        let field = record
+                            .get(index)
+                            .map(|field| string(field.to_string()))
+                            .unwrap_or_else(null);
+
+                        (name.clone(), field)
+                    })
+                    .collect(),
+            ),
+            None => array(
+                record
+                    .iter()
+                    .map(|field| string(field.to_string()))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn collect(mut self, function: &str) -> Result<ValueType> {
+        let mut rows = Vec::new();
+
+        while let Some(row) = self.next(function)? {
+            rows.push(row);
+        }
+
+        Ok(array(rows))
+    }
+}
+
+fn open(metadata: &Metadata, function: &str, args: &[ValueType]) -> Result<File> {
+    let path = fs::readable(metadata, function, args, 0)?;
+
+    File::open(&path).map_err(|e| fs::io_error(function, &path, e))
+}
+
+/// `CSV.parse(text, options)`
+fn parse(_metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let text = string_argument("CSV.parse", &args, 0)?;
+    let options = Options::parse("CSV.parse", &args, 1)?;
+
+    Rows::new("CSV.parse", options.reader(text.as_bytes()), &options)?.collect("CSV.parse")
+}
+
+/// `CSV.read(path, options)`
+fn read(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let file = open(metadata, "CSV.read", &args)?;
+    let options = Options::parse("CSV.read", &args, 1)?;
+
+    Rows::new("CSV.read", options.reader(file), &options)?.collect("CSV.read")
+}
+
+/// `CSV.each(path, fn, options)` calls the function with `(row, index)` for every row
+/// without loading the whole file.
+fn each(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let file = open(metadata, "CSV.each", &args)?;
+    let function = function_argument("CSV.each", &args, 1)?;
+    let options = Options::parse("CSV.each", &args, 2)?;
+
+    let mut rows = Rows::new("CSV.each", options.reader(file), &options)?;
+    let mut index = 0;
+
+    while let Some(row) = rows.next("CSV.each")? {
+        call_function(function.clone(), vec![row, number(Either::Left(index))])?;
+
+        index += 1;
+    }
+
+    Ok(undefined())
+}
+
+/// `CSV.reader(path, options)` returns a handle with `header` (`null` unless in header
+/// mode) and `next()`, which returns the next row or `null` at the end of the file.
+fn reader(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let file = open(metadata, "CSV.reader", &args)?;
+    let options = Options::parse("CSV.reader", &args, 1)?;
+
+    let rows = Rows::new("CSV.reader", options.reader(file), &options)?;
+
+    let header = match &rows.header {
+        Some(header) => array(header.iter().cloned().map(string).collect()),
+        None => null(),
+    };
+
+    let rows = Rc::new(RefCell::new(rows));
+
+    let mut entries = BTreeMap::new();
+
+    entries.insert("header".to_string(), header);
+    entries.insert(
+        "next".to_string(),
+        ValueType::InternalFunction(InternalFunction::from_closure(
+            "CSV.Reader.next",
+            move |_args| {
+                Ok(rows
+                    .borrow_mut()
+                    .next("CSV.Reader.next")?
+                    .unwrap_or_else(null))
+            },
+        )),
+    );
+
+    Ok(object(entries))
+}
+
+fn field(function: &str, value: &ValueType) -> Result<String> {
+    match value {
+        ValueType::Variable(PrimitiveTypes::Null | PrimitiveTypes::Undefined) => Ok(String::new()),
+        ValueType::Variable(
+            PrimitiveTypes::String(_)
+            | PrimitiveTypes::Number(_)
+            | PrimitiveTypes::Boolean(_)
+            | PrimitiveTypes::DateTime(_),
+        ) => Ok(value.to_string()),
+        value => Err(Error::r#type(&format!(
+            "{}() cannot write {} as a CSV field",
+            function,
+            value.type_name()
+        ))),
+    }
+}
+
+/// Rows are arrays of fields, or objects whose fields are picked by the `columns`
+/// option (the keys of the first row by default). A header row is written for objects
+/// unless `header` is false, and for arrays when `columns` is given.
+fn write_rows<W: io::Write>(
+    function: &str,
+    writer: &mut csv::Writer<W>,
+    rows: &[ValueType],
+    options: &Options,
+) -> Result<()> {
+    let objects = matches!(
+        rows.first(),
+        Some(ValueType::Variable(PrimitiveTypes::Object(_)))
+    );
+
+    let columns = match (&options.columns, rows.first()) {
+        (Some(columns), _) => Some(columns.clone()),
+        (None, Some(ValueType::Variable(PrimitiveTypes::Object(first)))) => {
+            Some(first.keys().cloned().collect())
+        }
+        _ => None,
+    };
+
+    if let Some(columns) = &columns {
+        if options
+            .header
+            .unwrap_or(objects || options.columns.is_some())
+        {
+            writer
+                .write_record(columns)
+                .map_err(|e| csv_error(function, e))?;
+        }
+    }
+
+    for row in rows {
+        let fields = match (row, &columns) {
+            (ValueType::Variable(PrimitiveTypes::Array(fields)), _) => fields
+                .iter()
+                .map(|value| field(function, value))
+                .collect::<Result<Vec<_>>>()?,
+            (ValueType::Variable(PrimitiveTypes::Object(entries)), Some(columns)) => columns
+                .iter()
+                .map(|column| {
+                    entries
+                        .get(column)
+                        .map_or(Ok(String::new()), |value| field(function, value))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            (row, _) => {
+                return Err(Error::r#type(&format!(
+                    "{}() expects rows to be arrays or objects, got {}",
+                    function,
+                    row.type_name()
+                )))
+            }
+        };
+
+        writer
+            .write_record(&fields)
+            .map_err(|e| csv_error(function, e))?;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| Error::io(&format!("{}() {}", function, e)))
+}
+
+/// `CSV.stringify(rows, options)`
+fn stringify(_metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let rows = array_argument("CSV.stringify", &args, 0)?;
+    let options = Options::parse("CSV.stringify", &args, 1)?;
+
+    let mut writer = options.writer(Vec::new());
+
+    write_rows("CSV.stringify", &mut writer, rows, &options)?;
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| Error::io(&format!("CSV.stringify() {}", e)))?;
+
+    Ok(string(String::from_utf8_lossy(&bytes).to_string()))
+}
+
+/// `CSV.write(path, rows, options)`
+fn write(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = fs::writable(metadata, "CSV.write", &args, 0)?;
+    let rows = array_argument("CSV.write", &args, 1)?;
+    let options = Options::parse("CSV.write", &args, 2)?;
+
+    let file = File::create(&path).map_err(|e| fs::io_error("CSV.write", &path, e))?;
+
+    write_rows("CSV.write", &mut options.writer(file), rows, &options)?;
+
+    Ok(undefined())
+}
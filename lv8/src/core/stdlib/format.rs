@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{as_f64, string, string_argument, undefined};
+use crate::core::{scope::ValueType, PrimitiveTypes};
+
+/// Larger widths and precisions are rejected, they only serve to exhaust memory.
+const MAX_WIDTH: usize = 10_000;
+const MAX_PRECISION: usize = 1_000;
+
+/// A parsed `[[fill]align][sign][#][0][width][grouping][.precision][type]` spec.
+struct Spec {
+    fill: char,
+    align: Option<char>,
+    sign: char,
+    alternate: bool,
+    width: usize,
+    grouping: Option<char>,
+    precision: Option<usize>,
+    kind: Option<char>,
+}
+
+impl Spec {
+    fn parse(function: &str, spec: &str) -> Result<Self> {
+        let invalid = || Error::value(&format!("{}() invalid format spec {:?}", function, spec));
+        let too_large = |name: &str, limit: usize| {
+            Error::value(&format!(
+                "{}() format spec {:?} {} is larger than {}",
+                function, spec, name, limit
+            ))
+        };
+
+        let chars: Vec<char> = spec.chars().collect();
+        let mut index = 0;
+
+        let mut result = Self {
+            fill: ' ',
+            align: None,
+            sign: '-',
+            alternate: false,
+            width: 0,
+            grouping: None,
+            precision: None,
+            kind: None,
+        };
+
+        let is_align = |char: Option<&char>| matches!(char, Some('<' | '>' | '^' | '='));
+
+        if is_align(chars.get(1)) {
+            result.fill = chars[0];
+            result.align = Some(chars[1]);
+            index = 2;
+        } else if is_align(chars.first()) {
+            result.align = Some(chars[0]);
+            index = 1;
+        }
+
+        if let Some(sign @ ('+' | '-' | ' ')) = chars.get(index) {
+            result.sign = *sign;
+            index += 1;
+        }
+
+        if chars.get(index) == Some(&'#') {
+            result.alternate = true;
+            index += 1;
+        }
+
+        // A leading zero pads numbers with zeros after the sign, like `=` with `0` fill.
+        if chars.get(index) == Some(&'0') {
+            if result.align.is_none() {
+                result.fill = '0';
+                result.align = Some('=');
+            }
+
+            index += 1;
+        }
+
+        let digits = |index: &mut usize| {
+            let start = *index;
+
+            while chars.get(*index).is_some_and(char::is_ascii_digit) {
+                *index += 1;
+            }
+
+            chars[start..*index].iter().collect::<String>()
+        };
+
+        let width = digits(&mut index);
+
+        if !width.is_empty() {
+            result.width = width.parse().map_err(|_| too_large("width", MAX_WIDTH))?;
+
+            if result.width > MAX_WIDTH {
+                return Err(too_large("width", MAX_WIDTH));
+            }
+        }
+
+        if let Some(grouping @ (',' | '_')) = chars.get(index) {
+            result.grouping = Some(*grouping);
+            index += 1;
+        }
+
+        if chars.get(index) == Some(&'.') {
+            index += 1;
+
+            let precision = digits(&mut index);
+
+            if precision.is_empty() {
+                return Err(invalid());
+            }
+
+            let precision = precision
+                .parse()
+                .map_err(|_| too_large("precision", MAX_PRECISION))?;
+
+            if precision > MAX_PRECISION {
+                return Err(too_large("precision", MAX_PRECISION));
+            }
+
+            result.precision = Some(precision);
+        }
+
+        if let Some(
+            kind @ ('s' | 'd' | 'f' | 'F' | 'e' | 'E' | 'g' | 'G' | '%' | 'x' | 'X' | 'o' | 'b'),
+        ) = chars.get(index)
+        {
+            result.kind = Some(*kind);
+            index += 1;
+        }
+
+        if index != chars.len() {
+            return Err(invalid());
+        }
+
+        Ok(result)
+    }
+}
+
+/// Inserts `separator` every `size` digits from the right.
+fn group(digits: &str, separator: char, size: usize) -> String {
+    let count = digits.chars().count();
+    let mut result = String::new();
+
+    for (index, char) in digits.chars().enumerate() {
+        if index > 0 && (count - index) % size == 0 {
+            result.push(separator);
+        }
+
+        result.push(char);
+    }
+
+    result
+}
+
+/// Formats like `{:e}` in Python, the exponent has a sign and at least two digits.
+fn scientific(value: f64, precision: usize, upper: bool) -> String {
+    let formatted = format!("{:.*e}", precision, value);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+
+    let (sign, exponent) = match exponent.strip_prefix('-') {
+        Some(exponent) => ('-', exponent),
+        None => ('+', exponent),
+    };
+
+    let result = format!("{}e{}{:0>2}", mantissa, sign, exponent);
+
+    if upper {
+        result.to_uppercase()
+    } else {
+        result
+    }
+}
+
+/// The shorter of fixed and scientific notation, with trailing zeros removed unless
+/// `alternate` is set.
+fn general(value: f64, precision: usize, alternate: bool, upper: bool) -> String {
+    let precision = precision.max(1);
+
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    let exponent = scientific(value, precision - 1, false)
+        .split_once('e')
+        .and_then(|(_, exponent)| exponent.parse::<i32>().ok())
+        .unwrap_or(0);
+
+    let strip = |text: String| {
+        if alternate || !text.contains('.') {
+            text
+        } else {
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+    };
+
+    if -4 <= exponent && exponent < precision as i32 {
+        strip(format!(
+            "{:.*}",
+            (precision as i32 - 1 - exponent) as usize,
+            value
+        ))
+    } else {
+        let formatted = scientific(value, precision - 1, upper);
+        let (mantissa, exponent) = formatted.split_once(['e', 'E']).unwrap();
+        let marker = if upper { 'E' } else { 'e' };
+
+        format!("{}{}{}", strip(mantissa.to_string()), marker, exponent)
+    }
+}
+
+fn non_finite(value: f64, upper: bool) -> Option<String> {
+    let text = if value.is_nan() {
+        "nan"
+    } else if value.is_infinite() {
+        "inf"
+    } else {
+        return None;
+    };
+
+    Some(if upper {
+        text.to_uppercase()
+    } else {
+        text.to_string()
+    })
+}
+
+fn type_mismatch(function: &str, value: &ValueType, kind: char) -> Error {
+    Error::r#type(&format!(
+        "{}() cannot format {} with {:?}",
+        function,
+        value.type_name(),
+        kind
+    ))
+}
+
+/// Returns whether the number is negative, its `0x`-like prefix and the digits, so
+/// padding can go in between.
+fn format_number(
+    function: &str,
+    value: &ValueType,
+    number: &Either<isize, f64>,
+    spec: &Spec,
+) -> Result<(bool, &'static str, String)> {
+    let float = as_f64(number);
+    let negative = float.is_sign_negative() && !float.is_nan();
+    let magnitude = float.abs();
+
+    let mut prefix = "";
+
+    let body = match (spec.kind, number) {
+        (Some('d') | None, Either::Left(integer)) => {
+            let digits = integer.unsigned_abs().to_string();
+
+            match spec.grouping {
+                Some(separator) => group(&digits, separator, 3),
+                None => digits,
+            }
+        }
+        (Some(kind @ ('x' | 'X' | 'o' | 'b')), Either::Left(integer)) => {
+            let integer = integer.unsigned_abs();
+
+            let (digits, alternate_prefix) = match kind {
+                'x' => (format!("{:x}", integer), "0x"),
+                'X' => (format!("{:X}", integer), "0X"),
+                'o' => (format!("{:o}", integer), "0o"),
+                _ => (format!("{:b}", integer), "0b"),
+            };
+
+            let digits = match spec.grouping {
+                Some('_') => group(&digits, '_', 4),
+                Some(_) => {
+                    return Err(Error::value(&format!(
+                        "{}() cannot use ',' with {:?}",
+                        function, kind
+                    )))
+                }
+                None => digits,
+            };
+
+            if spec.alternate {
+                prefix = alternate_prefix;
+            }
+
+            digits
+        }
+        (Some(kind @ ('d' | 'x' | 'X' | 'o' | 'b')), Either::Right(_)) => {
+            return Err(type_mismatch(function, value, kind))
+        }
+        (kind, _) => {
+            let upper = matches!(kind, Some('F' | 'E' | 'G'));
+
+            let formatted = match non_finite(magnitude, upper) {
+                Some(text) => text,
+                None => match kind {
+                    Some('f' | 'F') => format!("{:.*}", spec.precision.unwrap_or(6), magnitude),
+                    Some('e' | 'E') => scientific(magnitude, spec.precision.unwrap_or(6), upper),
+                    Some('g' | 'G') => general(
+                        magnitude,
+                        spec.precision.unwrap_or(6),
+                        spec.alternate,
+                        upper,
+                    ),
+                    Some('%') => {
+                        format!("{:.*}%", spec.precision.unwrap_or(6), magnitude * 100.0)
+                    }
+                    _ => match spec.precision {
+                        Some(precision) => format!("{:.*}", precision, magnitude),
+                        None => format!("{:?}", magnitude),
+                    },
+                },
+            };
+
+            match spec.grouping {
+                Some(separator) => {
+                    let split = formatted
+                        .find(|char: char| !char.is_ascii_digit())
+                        .unwrap_or(formatted.len());
+
+                    format!(
+                        "{}{}",
+                        group(&formatted[..split], separator, 3),
+                        &formatted[split..]
+                    )
+                }
+                None => formatted,
+            }
+        }
+    };
+
+    Ok((negative, prefix, body))
+}
+
+fn pad(text: String, sign: &str, spec: &Spec, default_align: char) -> String {
+    let length = sign.chars().count() + text.chars().count();
+
+    if length >= spec.width {
+        return format!("{}{}", sign, text);
+    }
+
+    let padding = spec.width - length;
+    let fill = |count: usize| spec.fill.to_string().repeat(count);
+
+    match spec.align.unwrap_or(default_align) {
+        '<' => format!("{}{}{}", sign, text, fill(padding)),
+        '^' => format!(
+            "{}{}{}{}",
+            fill(padding / 2),
+            sign,
+            text,
+            fill(padding - padding / 2)
+        ),
+        '=' => format!("{}{}{}", sign, fill(padding), text),
+        _ => format!("{}{}{}", fill(padding), sign, text),
+    }
+}
+
+fn format_value(function: &str, value: &ValueType, spec: &Spec) -> Result<String> {
+    match value {
+        ValueType::Variable(PrimitiveTypes::Number(number)) if spec.kind != Some('s') => {
+            let (negative, prefix, body) = format_number(function, value, number, spec)?;
+
+            let sign = match (negative, spec.sign) {
+                (true, _) => "-",
+                (false, '+') => "+",
+                (false, ' ') => " ",
+                _ => "",
+            };
+
+            Ok(pad(body, &format!("{}{}", sign, prefix), spec, '>'))
+        }
+        value => {
+            if let Some(kind) = spec.kind.filter(|kind| *kind != 's') {
+                return Err(type_mismatch(function, value, kind));
+            }
+
+            if spec.align == Some('=') {
+                return Err(Error::value(&format!(
+                    "{}() '=' alignment is only allowed for numbers",
+                    function
+                )));
+            }
+
+            let text = value.to_string();
+
+            let text = match spec.precision {
+                Some(precision) => text.chars().take(precision).collect(),
+                None => text,
+            };
+
+            Ok(pad(text, "", spec, '<'))
+        }
+    }
+}
+
+/// Formats `template` with `args`. Fields are `{}` (the next argument), `{0}` (an
+/// argument by position) or `{name}` (a key of the last argument, which must be an
+/// object), optionally followed by `:spec`. `{{` and `}}` are literal braces.
+pub fn format_template(function: &str, template: &str, args: &[ValueType]) -> Result<String> {
+    let invalid = |message: &str| Error::value(&format!("{}() {}", function, message));
+
+    let named: Option<&BTreeMap<String, ValueType>> = match args.last() {
+        Some(ValueType::Variable(PrimitiveTypes::Object(entries))) => Some(entries),
+        _ => None,
+    };
+
+    let mut result = String::new();
+    let mut chars = template.chars().peekable();
+    let mut next_index = 0;
+
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '}' => return Err(invalid("single '}' in template")),
+            '{' => {
+                let mut field = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') | None => return Err(invalid("unclosed '{' in template")),
+                        Some(char) => field.push(char),
+                    }
+                }
+
+                let (name, spec) = field.split_once(':').unwrap_or((&field, ""));
+
+                let value = if name.is_empty() {
+                    next_index += 1;
+
+                    args.get(next_index - 1).ok_or_else(|| {
+                        invalid(&format!("missing argument at position {}", next_index))
+                    })?
+                } else if let Ok(index) = name.parse::<usize>() {
+                    args.get(index).ok_or_else(|| {
+                        invalid(&format!("missing argument at position {}", index + 1))
+                    })?
+                } else {
+                    named
+                        .and_then(|named| named.get(name))
+                        .ok_or_else(|| invalid(&format!("missing named argument {:?}", name)))?
+                };
+
+                result.push_str(&format_value(
+                    function,
+                    value,
+                    &Spec::parse(function, spec)?,
+                )?);
+            }
+            char => result.push(char),
+        }
+    }
+
+    Ok(result)
+}
+
+/// `format(template, ...args)`
+pub fn format(args: Vec<ValueType>) -> Result<ValueType> {
+    let template = string_argument("format", &args, 0)?;
+
+    Ok(string(format_template("format", template, &args[1..])?))
+}
+
+/// Like `format`, but prints the result without a trailing new line.
+pub fn printf(args: Vec<ValueType>) -> Result<ValueType> {
+    let template = string_argument("printf", &args, 0)?;
+
+    print!("{}", format_template("printf", template, &args[1..])?);
+
+    Ok(undefined())
+}
@@ -0,0 +1,277 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{array, boolean, number, object, string, string_argument, undefined};
+use crate::core::{
+    capabilities::PathAccess, function::MetadataFunction, scope::ValueType, Metadata,
+    PrimitiveTypes,
+};
+
+/// Relative paths are resolved against the directory of the running file, like imports.
+pub const FUNCTIONS: &[(&str, MetadataFunction)] = &[
+    ("read_text", read_text),
+    ("write_text", write_text),
+    ("append_text", append_text),
+    ("read_lines", read_lines),
+    ("exists", exists),
+    ("is_file", is_file),
+    ("is_dir", is_dir),
+    ("list_dir", list_dir),
+    ("mkdir", mkdir),
+    ("remove", remove),
+    ("rename", rename),
+    ("copy", copy),
+    ("stat", stat),
+    ("glob", glob),
+];
+
+fn resolve(
+    metadata: &Metadata,
+    access: &PathAccess,
+    function: &str,
+    path: &str,
+) -> Result<PathBuf> {
+    let resolved = Path::new(&metadata.pw).join(path);
+
+    if !access.allows(&resolved) {
+        return Err(Error::permission(&format!(
+            "{}() is not allowed to access {:?}",
+            function, path
+        )));
+    }
+
+    Ok(resolved)
+}
+
+pub(super) fn readable(
+    metadata: &Metadata,
+    function: &str,
+    args: &[ValueType],
+    index: usize,
+) -> Result<PathBuf> {
+    let path = string_argument(function, args, index)?;
+
+    resolve(metadata, &metadata.capabilities.fs_read, function, path)
+}
+
+pub(super) fn writable(
+    metadata: &Metadata,
+    function: &str,
+    args: &[ValueType],
+    index: usize,
+) -> Result<PathBuf> {
+    let path = string_argument(function, args, index)?;
+
+    resolve(metadata, &metadata.capabilities.fs_write, function, path)
+}
+
+pub(super) fn io_error(function: &str, path: &Path, error: std::io::Error) -> Error {
+    Error::io(&format!("{}() {}: {}", function, path.display(), error))
+}
+
+fn read_text(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = readable(metadata, "FS.read_text", &args, 0)?;
+
+    fs::read_to_string(&path)
+        .map(string)
+        .map_err(|e| io_error("FS.read_text", &path, e))
+}
+
+fn write_text(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = writable(metadata, "FS.write_text", &args, 0)?;
+    let text = string_argument("FS.write_text", &args, 1)?;
+
+    fs::write(&path, text).map_err(|e| io_error("FS.write_text", &path, e))?;
+
+    Ok(undefined())
+}
+
+fn append_text(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = writable(metadata, "FS.append_text", &args, 0)?;
+    let text = string_argument("FS.append_text", &args, 1)?;
+
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|e| io_error("FS.append_text", &path, e))?;
+
+    Ok(undefined())
+}
+
+fn read_lines(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = readable(metadata, "FS.read_lines", &args, 0)?;
+
+    let text = fs::read_to_string(&path).map_err(|e| io_error("FS.read_lines", &path, e))?;
+
+    Ok(array(
+        text.lines().map(|line| string(line.to_string())).collect(),
+    ))
+}
+
+fn exists(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = readable(metadata, "FS.exists", &args, 0)?;
+
+    Ok(boolean(path.exists()))
+}
+
+fn is_file(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = readable(metadata, "FS.is_file", &args, 0)?;
+
+    Ok(boolean(path.is_file()))
+}
+
+fn is_dir(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = readable(metadata, "FS.is_dir", &args, 0)?;
+
+    Ok(boolean(path.is_dir()))
+}
+
+/// Returns the sorted names of the entries of a directory.
+fn list_dir(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = readable(metadata, "FS.list_dir", &args, 0)?;
+
+    let mut names = fs::read_dir(&path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+                .collect::<std::io::Result<Vec<String>>>()
+        })
+        .map_err(|e| io_error("FS.list_dir", &path, e))?;
+
+    names.sort();
+
+    Ok(array(names.into_iter().map(string).collect()))
+}
+
+/// Creates the directory and its missing parents, like `mkdir -p`.
+fn mkdir(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = writable(metadata, "FS.mkdir", &args, 0)?;
+
+    fs::create_dir_all(&path).map_err(|e| io_error("FS.mkdir", &path, e))?;
+
+    Ok(undefined())
+}
+
+/// `FS.remove(path, recursive)`, directories must be empty unless `recursive` is true.
+fn remove(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = writable(metadata, "FS.remove", &args, 0)?;
+    let recursive = matches!(
+        args.get(1),
+        Some(ValueType::Variable(PrimitiveTypes::Boolean(true)))
+    );
+
+    let result = if path.is_dir() {
+        if recursive {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_dir(&path)
+        }
+    } else {
+        fs::remove_file(&path)
+    };
+
+    result.map_err(|e| io_error("FS.remove", &path, e))?;
+
+    Ok(undefined())
+}
+
+fn rename(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let from = writable(metadata, "FS.rename", &args, 0)?;
+    let to = writable(metadata, "FS.rename", &args, 1)?;
+
+    fs::rename(&from, &to).map_err(|e| io_error("FS.rename", &from, e))?;
+
+    Ok(undefined())
+}
+
+fn copy(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let from = readable(metadata, "FS.copy", &args, 0)?;
+    let to = writable(metadata, "FS.copy", &args, 1)?;
+
+    fs::copy(&from, &to).map_err(|e| io_error("FS.copy", &from, e))?;
+
+    Ok(undefined())
+}
+
+/// Returns `{size, modified, is_file, is_dir, readonly}`, `modified` is in seconds since
+/// the unix epoch.
+fn stat(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = readable(metadata, "FS.stat", &args, 0)?;
+
+    let stat = fs::metadata(&path).map_err(|e| io_error("FS.stat", &path, e))?;
+
+    let modified = stat
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| number(Either::Right(modified.as_secs_f64())))
+        .unwrap_or_else(undefined);
+
+    let mut entries = BTreeMap::new();
+
+    entries.insert(
+        "size".to_string(),
+        number(Either::Left(stat.len() as isize)),
+    );
+    entries.insert("modified".to_string(), modified);
+    entries.insert("is_file".to_string(), boolean(stat.is_file()));
+    entries.insert("is_dir".to_string(), boolean(stat.is_dir()));
+    entries.insert(
+        "readonly".to_string(),
+        boolean(stat.permissions().readonly()),
+    );
+
+    Ok(object(entries))
+}
+
+/// Returns the sorted paths matching a pattern like `"data/**/*.csv"`, relative patterns
+/// give relative paths. Paths outside the readable roots are left out.
+fn glob(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let pattern = string_argument("FS.glob", &args, 0)?;
+
+    if metadata.capabilities.fs_read.is_denied() {
+        return Err(Error::permission("FS.glob() is not allowed to read files"));
+    }
+
+    let base = Path::new(&metadata.pw);
+    let absolute = base.join(pattern);
+
+    let paths = glob::glob(&absolute.to_string_lossy())
+        .map_err(|e| Error::value(&format!("FS.glob() invalid pattern {:?}: {}", pattern, e)))?;
+
+    let mut matches = Vec::new();
+
+    for path in paths {
+        let path = path.map_err(|e| {
+            let path = e.path().to_path_buf();
+
+            io_error("FS.glob", &path, e.into())
+        })?;
+
+        if !metadata.capabilities.fs_read.allows(&path) {
+            continue;
+        }
+
+        let path = if Path::new(pattern).is_relative() {
+            path.strip_prefix(base).unwrap_or(&path).to_path_buf()
+        } else {
+            path
+        };
+
+        matches.push(path.to_string_lossy().to_string());
+    }
+
+    matches.sort();
+
+    Ok(array(matches.into_iter().map(string).collect()))
+}
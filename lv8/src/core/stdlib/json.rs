@@ -0,0 +1,93 @@
+use serde_json::{Map, Number, Value};
+
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{argument, array, boolean, null, number, object, string, string_argument};
+use crate::core::{
+    expression::value_to_bool, function::NativeFunction, scope::ValueType, PrimitiveTypes,
+};
+
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[("encode", encode), ("decode", decode)];
+
+/// Parses JSON text into LV8 values, integers that fit stay integers.
+pub fn parse(function: &str, text: &str) -> Result<ValueType> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| Error::value(&format!("{}() invalid JSON: {}", function, e)))?;
+
+    Ok(from_json(value))
+}
+
+fn from_json(value: Value) -> ValueType {
+    match value {
+        Value::Null => null(),
+        Value::Bool(value) => boolean(value),
+        Value::Number(value) => {
+            match value.as_i64().and_then(|value| isize::try_from(value).ok()) {
+                Some(value) => number(Either::Left(value)),
+                None => number(Either::Right(value.as_f64().unwrap_or(f64::NAN))),
+            }
+        }
+        Value::String(value) => string(value),
+        Value::Array(values) => array(values.into_iter().map(from_json).collect()),
+        Value::Object(entries) => object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, from_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// `undefined` is encoded as `null` and datetimes as ISO-8601 strings.
+/// Functions, modules and non-finite floats have no JSON representation and
+/// raise a `TypeError`.
+fn to_json(value: &ValueType) -> Result<Value> {
+    match value {
+        ValueType::Variable(PrimitiveTypes::Null | PrimitiveTypes::Undefined) => Ok(Value::Null),
+        ValueType::Variable(PrimitiveTypes::Boolean(value)) => Ok(Value::Bool(*value)),
+        ValueType::Variable(PrimitiveTypes::Number(Either::Left(value))) => {
+            Ok(Value::Number(Number::from(*value as i64)))
+        }
+        ValueType::Variable(PrimitiveTypes::Number(Either::Right(value))) => {
+            Number::from_f64(*value).map(Value::Number).ok_or_else(|| {
+                Error::r#type(&format!("JSON.encode() cannot encode {} as JSON", value))
+            })
+        }
+        ValueType::Variable(PrimitiveTypes::String(value)) => Ok(Value::String(value.clone())),
+        ValueType::Variable(PrimitiveTypes::DateTime(_)) => Ok(Value::String(value.to_string())),
+        ValueType::Variable(PrimitiveTypes::Array(values)) => Ok(Value::Array(
+            values.iter().map(to_json).collect::<Result<_>>()?,
+        )),
+        ValueType::Variable(PrimitiveTypes::Object(entries)) => Ok(Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), to_json(value)?)))
+                .collect::<Result<Map<String, Value>>>()?,
+        )),
+        ValueType::Function(_) | ValueType::InternalFunction(_) | ValueType::Module(_) => Err(
+            Error::r#type(&format!("JSON.encode() cannot encode {} as JSON", value)),
+        ),
+    }
+}
+
+/// `JSON.encode(value, pretty)`, pretty printed output is indented with two spaces.
+fn encode(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = to_json(argument("JSON.encode", &args, 0)?)?;
+    let pretty = args.get(1).cloned().is_some_and(value_to_bool);
+
+    let text = if pretty {
+        serde_json::to_string_pretty(&value)
+    } else {
+        serde_json::to_string(&value)
+    };
+
+    text.map(string)
+        .map_err(|e| Error::r#type(&format!("JSON.encode() {}", e)))
+}
+
+fn decode(args: Vec<ValueType>) -> Result<ValueType> {
+    let text = string_argument("JSON.decode", &args, 0)?;
+
+    parse("JSON.decode", text)
+}
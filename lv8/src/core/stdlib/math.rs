@@ -0,0 +1,308 @@
+use std::cmp::Ordering;
+
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{argument, as_f64, number, number_argument};
+use crate::core::{function::NativeFunction, scope::ValueType, PrimitiveTypes};
+
+pub fn build_module() -> ValueType {
+    let functions: &[(&str, NativeFunction)] = &[
+        ("abs", abs),
+        ("floor", floor),
+        ("ceil", ceil),
+        ("round", round),
+        ("sqrt", sqrt),
+        ("pow", pow),
+        ("exp", exp),
+        ("log", log),
+        ("log10", log10),
+        ("log2", log2),
+        ("sin", sin),
+        ("cos", cos),
+        ("tan", tan),
+        ("asin", asin),
+        ("acos", acos),
+        ("atan", atan),
+        ("atan2", atan2),
+        ("sinh", sinh),
+        ("cosh", cosh),
+        ("tanh", tanh),
+        ("asinh", asinh),
+        ("acosh", acosh),
+        ("atanh", atanh),
+        ("min", min),
+        ("max", max),
+        ("clamp", clamp),
+        ("gcd", gcd),
+        ("lcm", lcm),
+    ];
+
+    let constants = vec![
+        ("pi", number(Either::Right(std::f64::consts::PI))),
+        ("e", number(Either::Right(std::f64::consts::E))),
+        ("inf", number(Either::Right(f64::INFINITY))),
+        ("nan", number(Either::Right(f64::NAN))),
+    ];
+
+    super::build_module("Math", functions, constants)
+}
+
+/// Rounded floats become integers again when they fit, so `Math.floor(2.5)` is `2`
+/// and can be used wherever an integer is expected.
+fn float_to_integer(value: f64) -> Either<isize, f64> {
+    if value.is_finite() && value >= isize::MIN as f64 && value < isize::MAX as f64 {
+        Either::Left(value as isize)
+    } else {
+        Either::Right(value)
+    }
+}
+
+fn compare(left: &Either<isize, f64>, right: &Either<isize, f64>) -> Option<Ordering> {
+    match (left, right) {
+        (Either::Left(left), Either::Left(right)) => Some(left.cmp(right)),
+        _ => as_f64(left).partial_cmp(&as_f64(right)),
+    }
+}
+
+fn overflow(function: &str) -> Error {
+    Error::value(&format!("{}() integer overflow", function))
+}
+
+fn float_function(function: &str, args: &[ValueType], f: fn(f64) -> f64) -> Result<ValueType> {
+    let value = number_argument(function, args, 0)?;
+
+    Ok(number(Either::Right(f(as_f64(&value)))))
+}
+
+fn rounding_function(function: &str, args: &[ValueType], f: fn(f64) -> f64) -> Result<ValueType> {
+    match number_argument(function, args, 0)? {
+        Either::Left(value) => Ok(number(Either::Left(value))),
+        Either::Right(value) => Ok(number(float_to_integer(f(value)))),
+    }
+}
+
+fn abs(args: Vec<ValueType>) -> Result<ValueType> {
+    match number_argument("Math.abs", &args, 0)? {
+        Either::Left(value) => value
+            .checked_abs()
+            .map(|value| number(Either::Left(value)))
+            .ok_or_else(|| overflow("Math.abs")),
+        Either::Right(value) => Ok(number(Either::Right(value.abs()))),
+    }
+}
+
+fn floor(args: Vec<ValueType>) -> Result<ValueType> {
+    rounding_function("Math.floor", &args, f64::floor)
+}
+
+fn ceil(args: Vec<ValueType>) -> Result<ValueType> {
+    rounding_function("Math.ceil", &args, f64::ceil)
+}
+
+fn round(args: Vec<ValueType>) -> Result<ValueType> {
+    rounding_function("Math.round", &args, f64::round)
+}
+
+fn sqrt(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.sqrt", &args, f64::sqrt)
+}
+
+fn pow(args: Vec<ValueType>) -> Result<ValueType> {
+    let base = number_argument("Math.pow", &args, 0)?;
+    let exponent = number_argument("Math.pow", &args, 1)?;
+
+    if let (Either::Left(base), Either::Left(exponent)) = (&base, &exponent) {
+        if let Ok(exponent) = u32::try_from(*exponent) {
+            return base
+                .checked_pow(exponent)
+                .map(|value| number(Either::Left(value)))
+                .ok_or_else(|| overflow("Math.pow"));
+        }
+    }
+
+    Ok(number(Either::Right(as_f64(&base).powf(as_f64(&exponent)))))
+}
+
+fn exp(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.exp", &args, f64::exp)
+}
+
+fn log(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = as_f64(&number_argument("Math.log", &args, 0)?);
+
+    if args.len() > 1 {
+        let base = as_f64(&number_argument("Math.log", &args, 1)?);
+
+        return Ok(number(Either::Right(value.log(base))));
+    }
+
+    Ok(number(Either::Right(value.ln())))
+}
+
+fn log10(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.log10", &args, f64::log10)
+}
+
+fn log2(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.log2", &args, f64::log2)
+}
+
+fn sin(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.sin", &args, f64::sin)
+}
+
+fn cos(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.cos", &args, f64::cos)
+}
+
+fn tan(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.tan", &args, f64::tan)
+}
+
+fn asin(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.asin", &args, f64::asin)
+}
+
+fn acos(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.acos", &args, f64::acos)
+}
+
+fn atan(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.atan", &args, f64::atan)
+}
+
+fn atan2(args: Vec<ValueType>) -> Result<ValueType> {
+    let y = as_f64(&number_argument("Math.atan2", &args, 0)?);
+    let x = as_f64(&number_argument("Math.atan2", &args, 1)?);
+
+    Ok(number(Either::Right(y.atan2(x))))
+}
+
+fn sinh(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.sinh", &args, f64::sinh)
+}
+
+fn cosh(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.cosh", &args, f64::cosh)
+}
+
+fn tanh(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.tanh", &args, f64::tanh)
+}
+
+fn asinh(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.asinh", &args, f64::asinh)
+}
+
+fn acosh(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.acosh", &args, f64::acosh)
+}
+
+fn atanh(args: Vec<ValueType>) -> Result<ValueType> {
+    float_function("Math.atanh", &args, f64::atanh)
+}
+
+/// `min` and `max` accept either several numbers or a single array of numbers.
+fn extremum(function: &str, args: Vec<ValueType>, wanted: Ordering) -> Result<ValueType> {
+    let values = match args.as_slice() {
+        [ValueType::Variable(PrimitiveTypes::Array(values))] => values.clone(),
+        _ => args,
+    };
+
+    let mut result: Option<Either<isize, f64>> = None;
+
+    for index in 0..values.len() {
+        let value = number_argument(function, &values, index)?;
+
+        result = match result {
+            Some(current) if compare(&value, &current) != Some(wanted) => Some(current),
+            _ => Some(value),
+        };
+    }
+
+    result
+        .map(number)
+        .ok_or_else(|| Error::r#type(&format!("{}() expects at least one number", function)))
+}
+
+fn min(args: Vec<ValueType>) -> Result<ValueType> {
+    extremum("Math.min", args, Ordering::Less)
+}
+
+fn max(args: Vec<ValueType>) -> Result<ValueType> {
+    extremum("Math.max", args, Ordering::Greater)
+}
+
+fn clamp(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = number_argument("Math.clamp", &args, 0)?;
+    let low = number_argument("Math.clamp", &args, 1)?;
+    let high = number_argument("Math.clamp", &args, 2)?;
+
+    if compare(&low, &high) == Some(Ordering::Greater) {
+        return Err(Error::r#type(
+            "Math.clamp() lower bound is greater than upper bound",
+        ));
+    }
+
+    if compare(&value, &low) == Some(Ordering::Less) {
+        Ok(number(low))
+    } else if compare(&value, &high) == Some(Ordering::Greater) {
+        Ok(number(high))
+    } else {
+        Ok(number(value))
+    }
+}
+
+fn integer_arguments(function: &str, args: &[ValueType]) -> Result<Vec<isize>> {
+    argument(function, args, 0)?;
+
+    (0..args.len())
+        .map(|index| match number_argument(function, args, index)? {
+            Either::Left(value) => Ok(value),
+            Either::Right(_) => Err(Error::r#type(&format!(
+                "{}() expects integers, got a float at position {}",
+                function,
+                index + 1
+            ))),
+        })
+        .collect()
+}
+
+fn gcd_of(mut left: usize, mut right: usize) -> usize {
+    while right != 0 {
+        (left, right) = (right, left % right);
+    }
+
+    left
+}
+
+fn gcd(args: Vec<ValueType>) -> Result<ValueType> {
+    let result = integer_arguments("Math.gcd", &args)?
+        .into_iter()
+        .fold(0, |result, value| gcd_of(result, value.unsigned_abs()));
+
+    isize::try_from(result)
+        .map(|value| number(Either::Left(value)))
+        .map_err(|_| overflow("Math.gcd"))
+}
+
+fn lcm(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut result: usize = 1;
+
+    for value in integer_arguments("Math.lcm", &args)? {
+        let value = value.unsigned_abs();
+
+        if value == 0 {
+            return Ok(number(Either::Left(0)));
+        }
+
+        result = (result / gcd_of(result, value))
+            .checked_mul(value)
+            .ok_or_else(|| overflow("Math.lcm"))?;
+    }
+
+    isize::try_from(result)
+        .map(|value| number(Either::Left(value)))
+        .map_err(|_| overflow("Math.lcm"))
+}
@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use lv8_common::error::{Error, Result};
+
+use super::{
+    argument, array, array_argument, boolean, function_argument, object, object_argument, string,
+    string_argument, undefined,
+};
+use crate::core::{
+    expression::value_to_bool,
+    function::{call_function, NativeFunction},
+    scope::ValueType,
+    PrimitiveTypes,
+};
+
+/// Also used for method calls on object values, the object is passed as the first
+/// argument. Properties of the object take precedence over these methods.
+///
+/// Objects are values, so `set` and `delete` return the updated object.
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[
+    ("keys", keys),
+    ("values", values),
+    ("entries", entries),
+    ("has_key", has_key),
+    ("get", get),
+    ("set", set),
+    ("delete", delete),
+    ("merge", merge),
+    ("deep_merge", deep_merge),
+    ("from_entries", from_entries),
+    ("map_values", map_values),
+    ("filter", filter),
+];
+
+fn keys(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Object.keys", &args, 0)?;
+
+    Ok(array(entries.keys().cloned().map(string).collect()))
+}
+
+fn values(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Object.values", &args, 0)?;
+
+    Ok(array(entries.values().cloned().collect()))
+}
+
+fn entries(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Object.entries", &args, 0)?;
+
+    Ok(array(
+        entries
+            .iter()
+            .map(|(key, value)| array(vec![string(key.clone()), value.clone()]))
+            .collect(),
+    ))
+}
+
+fn has_key(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Object.has_key", &args, 0)?;
+    let key = string_argument("Object.has_key", &args, 1)?;
+
+    Ok(boolean(entries.contains_key(key)))
+}
+
+/// `Object.get(object, key, default)`, `default` is `undefined` when not given.
+fn get(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Object.get", &args, 0)?;
+    let key = string_argument("Object.get", &args, 1)?;
+
+    match entries.get(key) {
+        Some(value) => Ok(value.clone()),
+        None => Ok(args.get(2).cloned().unwrap_or_else(undefined)),
+    }
+}
+
+fn set(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut entries = object_argument("Object.set", &args, 0)?.clone();
+    let key = string_argument("Object.set", &args, 1)?;
+    let value = argument("Object.set", &args, 2)?;
+
+    entries.insert(key.to_string(), value.clone());
+
+    Ok(object(entries))
+}
+
+fn delete(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut entries = object_argument("Object.delete", &args, 0)?.clone();
+    let key = string_argument("Object.delete", &args, 1)?;
+
+    entries.remove(key);
+
+    Ok(object(entries))
+}
+
+/// Merges objects from left to right, later keys override earlier ones.
+fn merge(args: Vec<ValueType>) -> Result<ValueType> {
+    let mut merged = object_argument("Object.merge", &args, 0)?.clone();
+
+    for index in 1..args.len() {
+        merged.extend(object_argument("Object.merge", &args, index)?.clone());
+    }
+
+    Ok(object(merged))
+}
+
+/// Like `merge`, but nested objects present on both sides are merged as well.
+fn deep_merge(args: Vec<ValueType>) -> Result<ValueType> {
+    fn merge_into(merged: &mut BTreeMap<String, ValueType>, other: &BTreeMap<String, ValueType>) {
+        for (key, value) in other {
+            match (merged.get_mut(key), value) {
+                (
+                    Some(ValueType::Variable(PrimitiveTypes::Object(left))),
+                    ValueType::Variable(PrimitiveTypes::Object(right)),
+                ) => merge_into(left, right),
+                _ => {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    let mut merged = object_argument("Object.deep_merge", &args, 0)?.clone();
+
+    for index in 1..args.len() {
+        merge_into(
+            &mut merged,
+            object_argument("Object.deep_merge", &args, index)?,
+        );
+    }
+
+    Ok(object(merged))
+}
+
+/// Builds an object from `[key, value]` pairs, the reverse of `Object.entries`.
+fn from_entries(args: Vec<ValueType>) -> Result<ValueType> {
+    let pairs = array_argument("Object.from_entries", &args, 0)?;
+
+    let mut entries = BTreeMap::new();
+
+    for pair in pairs {
+        match pair {
+            ValueType::Variable(PrimitiveTypes::Array(pair)) => match pair.as_slice() {
+                [ValueType::Variable(PrimitiveTypes::String(key)), value] => {
+                    entries.insert(key.clone(), value.clone());
+                }
+                _ => {
+                    return Err(Error::r#type(
+                        "Object.from_entries() expects [key, value] pairs with string keys",
+                    ))
+                }
+            },
+            pair => {
+                return Err(Error::r#type(&format!(
+                    "Object.from_entries() expects [key, value] pairs, got {}",
+                    pair.type_name()
+                )))
+            }
+        }
+    }
+
+    Ok(object(entries))
+}
+
+/// Calls the function with `(value, key)` for every entry and keeps the results.
+fn map_values(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Object.map_values", &args, 0)?;
+    let function = function_argument("Object.map_values", &args, 1)?;
+
+    let mut mapped = BTreeMap::new();
+
+    for (key, value) in entries {
+        let value = call_function(function.clone(), vec![value.clone(), string(key.clone())])?;
+
+        mapped.insert(key.clone(), value);
+    }
+
+    Ok(object(mapped))
+}
+
+/// Keeps the entries for which the function, called with `(value, key)`, is truthy.
+fn filter(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Object.filter", &args, 0)?;
+    let function = function_argument("Object.filter", &args, 1)?;
+
+    let mut filtered = BTreeMap::new();
+
+    for (key, value) in entries {
+        let keep = call_function(function.clone(), vec![value.clone(), string(key.clone())])?;
+
+        if value_to_bool(keep) {
+            filtered.insert(key.clone(), value.clone());
+        }
+    }
+
+    Ok(object(filtered))
+}
@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{function_argument, integer_argument, number, string, string_argument, undefined};
+use crate::core::{function::MetadataFunction, scope::ValueType, Metadata};
+
+pub const FUNCTIONS: &[(&str, MetadataFunction)] = &[
+    ("cwd", cwd),
+    ("chdir", chdir),
+    ("platform", platform),
+    ("pid", pid),
+    ("exit", exit),
+    ("at_exit", at_exit),
+];
+
+/// Only installed when the evaluator is allowed to access the environment.
+pub const ENV_FUNCTIONS: &[(&str, MetadataFunction)] = &[("env", env), ("set_env", set_env)];
+
+/// `OS.env(name, default)`, `default` is `undefined` when not given.
+fn env(_metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let name = string_argument("OS.env", &args, 0)?;
+
+    match std::env::var(name) {
+        Ok(value) => Ok(string(value)),
+        Err(_) => Ok(args.get(1).cloned().unwrap_or_else(undefined)),
+    }
+}
+
+fn set_env(_metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let name = string_argument("OS.set_env", &args, 0)?;
+    let value = string_argument("OS.set_env", &args, 1)?;
+
+    if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+        return Err(Error::value(&format!(
+            "OS.set_env() invalid variable {:?}",
+            name
+        )));
+    }
+
+    std::env::set_var(name, value);
+
+    Ok(undefined())
+}
+
+fn cwd(_metadata: &Metadata, _args: Vec<ValueType>) -> Result<ValueType> {
+    std::env::current_dir()
+        .map(|path| string(path.to_string_lossy().to_string()))
+        .map_err(|e| Error::io(&format!("OS.cwd() {}", e)))
+}
+
+/// Changes the working directory of the process, relative paths are resolved against
+/// the directory of the running file. Paths given to `FS` are not affected, and neither
+/// are the sandbox roots, which are resolved once at startup.
+fn chdir(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let path = string_argument("OS.chdir", &args, 0)?;
+    let resolved = Path::new(&metadata.pw).join(path);
+
+    if !metadata.capabilities.fs_read.allows(&resolved) {
+        return Err(Error::permission(&format!(
+            "OS.chdir() is not allowed to access {:?}",
+            path
+        )));
+    }
+
+    std::env::set_current_dir(&resolved)
+        .map_err(|e| Error::io(&format!("OS.chdir() {}: {}", resolved.display(), e)))?;
+
+    Ok(undefined())
+}
+
+/// The name of the operating system, e.g. `"linux"`, `"macos"` or `"windows"`.
+fn platform(_metadata: &Metadata, _args: Vec<ValueType>) -> Result<ValueType> {
+    Ok(string(std::env::consts::OS.to_string()))
+}
+
+fn pid(_metadata: &Metadata, _args: Vec<ValueType>) -> Result<ValueType> {
+    Ok(number(Either::Left(std::process::id() as isize)))
+}
+
+/// `OS.exit(code)`, `code` is 0 when not given. The exit unwinds to the interpreter,
+/// which runs the `at_exit` handlers before leaving.
+fn exit(_metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let code = if args.is_empty() {
+        0
+    } else {
+        integer_argument("OS.exit", &args, 0)?
+    };
+
+    let code = i32::try_from(code)
+        .map_err(|_| Error::value(&format!("OS.exit() invalid exit code {}", code)))?;
+
+    Err(Error::exit(code))
+}
+
+fn at_exit(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let handler = function_argument("OS.at_exit", &args, 0)?;
+
+    metadata.exit_handlers.borrow_mut().push(handler.clone());
+
+    Ok(undefined())
+}
@@ -0,0 +1,372 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Read, Write},
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio},
+    rc::Rc,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+use wait_timeout::ChildExt;
+
+use super::{
+    array_argument, as_f64, null, number, object, object_argument, string, string_argument,
+    undefined,
+};
+use crate::core::{
+    function::{InternalFunction, MetadataFunction},
+    scope::ValueType,
+    Metadata, PrimitiveTypes,
+};
+
+pub const FUNCTIONS: &[(&str, MetadataFunction)] = &[("run", run), ("spawn", spawn)];
+
+/// Options shared by `Process.run` and `Process.spawn`.
+struct Options {
+    cwd: Option<String>,
+    env: Vec<(String, String)>,
+    stdin: Option<String>,
+    timeout: Option<Duration>,
+}
+
+impl Options {
+    fn parse(function: &str, args: &[ValueType]) -> Result<Self> {
+        let mut options = Self {
+            cwd: None,
+            env: Vec::new(),
+            stdin: None,
+            timeout: None,
+        };
+
+        if args.len() < 3 {
+            return Ok(options);
+        }
+
+        for (key, value) in object_argument(function, args, 2)? {
+            match (key.as_str(), value) {
+                ("cwd", ValueType::Variable(PrimitiveTypes::String(cwd))) => {
+                    options.cwd = Some(cwd.clone());
+                }
+                ("env", ValueType::Variable(PrimitiveTypes::Object(env))) => {
+                    for (name, value) in env {
+                        match value {
+                            ValueType::Variable(PrimitiveTypes::String(value)) => {
+                                options.env.push((name.clone(), value.clone()));
+                            }
+                            value => return Err(option_error(function, "env", "strings", value)),
+                        }
+                    }
+                }
+                ("stdin", ValueType::Variable(PrimitiveTypes::String(stdin))) => {
+                    options.stdin = Some(stdin.clone());
+                }
+                ("timeout", ValueType::Variable(PrimitiveTypes::Number(timeout))) => {
+                    options.timeout =
+                        Some(Duration::try_from_secs_f64(as_f64(timeout)).map_err(|_| {
+                            Error::value(&format!(
+                                "{}() invalid timeout {}",
+                                function,
+                                ValueType::Variable(PrimitiveTypes::Number(timeout.clone()))
+                            ))
+                        })?);
+                }
+                ("cwd" | "stdin", value) => {
+                    return Err(option_error(function, key, "a string", value))
+                }
+                ("env", value) => return Err(option_error(function, key, "an object", value)),
+                ("timeout", value) => return Err(option_error(function, key, "a number", value)),
+                _ => {
+                    return Err(Error::value(&format!(
+                        "{}() unknown option {:?}",
+                        function, key
+                    )))
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Relative `cwd` options are resolved against the directory of the running file,
+    /// like `FS` paths.
+    fn command(&self, metadata: &Metadata, function: &str, args: &[ValueType]) -> Result<Command> {
+        let program = string_argument(function, args, 0)?;
+
+        let mut command = Command::new(program);
+
+        if args.len() > 1 {
+            let arguments = array_argument(function, args, 1)?;
+
+            for index in 0..arguments.len() {
+                command.arg(string_argument(function, arguments, index)?);
+            }
+        }
+
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(Path::new(&metadata.pw).join(cwd));
+        }
+
+        command.envs(self.env.iter().cloned());
+
+        Ok(command)
+    }
+}
+
+fn option_error(function: &str, option: &str, expected: &str, value: &ValueType) -> Error {
+    Error::r#type(&format!(
+        "{}() expects {} for option {:?}, got {}",
+        function,
+        expected,
+        option,
+        value.type_name()
+    ))
+}
+
+fn spawn_error(function: &str, args: &[ValueType], error: std::io::Error) -> Error {
+    Error::io(&format!(
+        "{}() cannot run {}: {}",
+        function,
+        args.first().map(ValueType::to_string).unwrap_or_default(),
+        error
+    ))
+}
+
+fn io_error(function: &str, error: std::io::Error) -> Error {
+    Error::io(&format!("{}() {}", function, error))
+}
+
+/// Reads a pipe to the end on another thread, so a child filling one pipe can't block
+/// while we are waiting on the other.
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+
+        output
+    })
+}
+
+fn output_text(output: Vec<u8>) -> ValueType {
+    string(String::from_utf8_lossy(&output).to_string())
+}
+
+/// The exit code, or `null` when the process was terminated by a signal.
+fn exit_status(status: ExitStatus) -> ValueType {
+    status
+        .code()
+        .map(|code| number(Either::Left(code as isize)))
+        .unwrap_or_else(null)
+}
+
+fn result(status: ExitStatus, stdout: Vec<u8>, stderr: Vec<u8>) -> ValueType {
+    let mut entries = BTreeMap::new();
+
+    entries.insert("status".to_string(), exit_status(status));
+    entries.insert("stdout".to_string(), output_text(stdout));
+    entries.insert("stderr".to_string(), output_text(stderr));
+
+    object(entries)
+}
+
+/// `Process.run(cmd, args, options)` runs a command to completion and returns
+/// `{status, stdout, stderr}`. Options: `cwd`, `env` (added to the current
+/// environment), `stdin` (text written to the process) and `timeout` in seconds.
+fn run(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let options = Options::parse("Process.run", &args)?;
+
+    let mut child = options
+        .command(metadata, "Process.run", &args)?
+        .stdin(if options.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| spawn_error("Process.run", &args, e))?;
+
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), options.stdin) {
+        thread::spawn(move || stdin.write_all(input.as_bytes()));
+    }
+
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let status = match options.timeout {
+        Some(timeout) => match child
+            .wait_timeout(timeout)
+            .map_err(|e| io_error("Process.run", e))?
+        {
+            Some(status) => status,
+            None => {
+                let _ = child.kill();
+                let _ = child.wait();
+
+                return Err(Error::io(&format!(
+                    "Process.run() timed out after {}s",
+                    timeout.as_secs_f64()
+                )));
+            }
+        },
+        None => child.wait().map_err(|e| io_error("Process.run", e))?,
+    };
+
+    Ok(result(
+        status,
+        stdout.join().unwrap_or_default(),
+        stderr.join().unwrap_or_default(),
+    ))
+}
+
+struct Spawned {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: Option<BufReader<ChildStdout>>,
+    stderr: Option<JoinHandle<Vec<u8>>>,
+}
+
+type HandleMethod = fn(&mut Spawned, Vec<ValueType>) -> Result<ValueType>;
+
+/// `Process.spawn(cmd, args, options)` starts a command and returns a handle with
+/// `pid`, `write(text)`, `close_stdin()`, `read_line()`, `wait()` and `kill()`.
+/// Accepts the same options as `Process.run`, except `stdin` and `timeout`.
+fn spawn(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let options = Options::parse("Process.spawn", &args)?;
+
+    if options.stdin.is_some() || options.timeout.is_some() {
+        return Err(Error::value(
+            "Process.spawn() doesn't support the \"stdin\" and \"timeout\" options",
+        ));
+    }
+
+    let mut child = options
+        .command(metadata, "Process.spawn", &args)?
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| spawn_error("Process.spawn", &args, e))?;
+
+    let pid = child.id();
+
+    let spawned = Rc::new(RefCell::new(Spawned {
+        stdin: child.stdin.take(),
+        stdout: child.stdout.take().map(BufReader::new),
+        stderr: Some(drain(child.stderr.take())),
+        child,
+    }));
+
+    let methods: &[(&str, HandleMethod)] = &[
+        ("write", write),
+        ("close_stdin", close_stdin),
+        ("read_line", read_line),
+        ("wait", wait),
+        ("kill", kill),
+    ];
+
+    let mut entries = BTreeMap::new();
+
+    entries.insert("pid".to_string(), number(Either::Left(pid as isize)));
+
+    for (name, method) in methods {
+        let spawned = Rc::clone(&spawned);
+
+        entries.insert(
+            name.to_string(),
+            ValueType::InternalFunction(InternalFunction::from_closure(
+                &format!("Process.Child.{}", name),
+                move |args| method(&mut spawned.borrow_mut(), args),
+            )),
+        );
+    }
+
+    Ok(object(entries))
+}
+
+fn write(spawned: &mut Spawned, args: Vec<ValueType>) -> Result<ValueType> {
+    let text = string_argument("Process.Child.write", &args, 0)?;
+
+    let stdin = spawned
+        .stdin
+        .as_mut()
+        .ok_or_else(|| Error::io("Process.Child.write() stdin is closed"))?;
+
+    stdin
+        .write_all(text.as_bytes())
+        .and_then(|_| stdin.flush())
+        .map_err(|e| io_error("Process.Child.write", e))?;
+
+    Ok(undefined())
+}
+
+/// Closing stdin signals end of input, commands like `cat` only exit after it.
+fn close_stdin(spawned: &mut Spawned, _args: Vec<ValueType>) -> Result<ValueType> {
+    spawned.stdin = None;
+
+    Ok(undefined())
+}
+
+/// Returns the next line of stdout without its line ending, or `null` at the end.
+fn read_line(spawned: &mut Spawned, _args: Vec<ValueType>) -> Result<ValueType> {
+    let Some(stdout) = spawned.stdout.as_mut() else {
+        return Ok(null());
+    };
+
+    let mut line = String::new();
+
+    if stdout
+        .read_line(&mut line)
+        .map_err(|e| io_error("Process.Child.read_line", e))?
+        == 0
+    {
+        return Ok(null());
+    }
+
+    let line = line.strip_suffix('\n').unwrap_or(&line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+
+    Ok(string(line.to_string()))
+}
+
+/// Closes stdin, waits for the process to exit and returns `{status, stdout, stderr}`
+/// with the output that wasn't read yet.
+fn wait(spawned: &mut Spawned, _args: Vec<ValueType>) -> Result<ValueType> {
+    spawned.stdin = None;
+
+    let mut stdout = Vec::new();
+
+    if let Some(mut pipe) = spawned.stdout.take() {
+        pipe.read_to_end(&mut stdout)
+            .map_err(|e| io_error("Process.Child.wait", e))?;
+    }
+
+    let stderr = spawned
+        .stderr
+        .take()
+        .map(|stderr| stderr.join().unwrap_or_default())
+        .unwrap_or_default();
+
+    let status = spawned
+        .child
+        .wait()
+        .map_err(|e| io_error("Process.Child.wait", e))?;
+
+    Ok(result(status, stdout, stderr))
+}
+
+fn kill(spawned: &mut Spawned, _args: Vec<ValueType>) -> Result<ValueType> {
+    spawned
+        .child
+        .kill()
+        .map_err(|e| io_error("Process.Child.kill", e))?;
+
+    Ok(undefined())
+}
@@ -0,0 +1,116 @@
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{array, array_argument, integer_argument, number, string, undefined};
+use crate::core::{function::MetadataFunction, scope::ValueType, Metadata};
+
+/// All functions share one generator per program, so a run can be reproduced with
+/// `--seed` or `Random.seed(n)`.
+pub const FUNCTIONS: &[(&str, MetadataFunction)] = &[
+    ("seed", seed),
+    ("int", int),
+    ("float", float),
+    ("choice", choice),
+    ("shuffle", shuffle),
+    ("sample", sample),
+    ("uuid4", uuid4),
+];
+
+fn seed(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let seed = integer_argument("Random.seed", &args, 0)?;
+
+    metadata.random.borrow_mut().seed(seed as u64);
+
+    Ok(undefined())
+}
+
+/// `Random.int(lo, hi)`, both bounds are included.
+fn int(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let low = integer_argument("Random.int", &args, 0)?;
+    let high = integer_argument("Random.int", &args, 1)?;
+
+    if low > high {
+        return Err(Error::value(&format!(
+            "Random.int() lower bound {} is greater than upper bound {}",
+            low, high
+        )));
+    }
+
+    Ok(number(Either::Left(
+        metadata.random.borrow_mut().isize(low..=high),
+    )))
+}
+
+/// A float between 0 (included) and 1 (excluded).
+fn float(metadata: &Metadata, _args: Vec<ValueType>) -> Result<ValueType> {
+    Ok(number(Either::Right(metadata.random.borrow_mut().f64())))
+}
+
+fn choice(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let values = array_argument("Random.choice", &args, 0)?;
+
+    if values.is_empty() {
+        return Err(Error::value(
+            "Random.choice() cannot choose from an empty array",
+        ));
+    }
+
+    let index = metadata.random.borrow_mut().usize(..values.len());
+
+    Ok(values[index].clone())
+}
+
+/// Returns a shuffled copy of the array.
+fn shuffle(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = array_argument("Random.shuffle", &args, 0)?.clone();
+
+    metadata.random.borrow_mut().shuffle(&mut values);
+
+    Ok(array(values))
+}
+
+/// `Random.sample(array, k)` picks `k` distinct elements, in random order.
+fn sample(metadata: &Metadata, args: Vec<ValueType>) -> Result<ValueType> {
+    let mut values = array_argument("Random.sample", &args, 0)?.clone();
+    let count = integer_argument("Random.sample", &args, 1)?;
+
+    if count < 0 || count as usize > values.len() {
+        return Err(Error::value(&format!(
+            "Random.sample() cannot take {} elements from an array of {}",
+            count,
+            values.len()
+        )));
+    }
+
+    let mut random = metadata.random.borrow_mut();
+
+    // Partial Fisher-Yates, the first `count` elements end up being the sample.
+    for index in 0..count as usize {
+        let other = random.usize(index..values.len());
+
+        values.swap(index, other);
+    }
+
+    values.truncate(count as usize);
+
+    Ok(array(values))
+}
+
+/// A random version 4 UUID, drawn from the same generator as the other functions.
+fn uuid4(metadata: &Metadata, _args: Vec<ValueType>) -> Result<ValueType> {
+    let mut bytes = metadata.random.borrow_mut().u128(..).to_be_bytes();
+
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    Ok(string(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )))
+}
@@ -0,0 +1,311 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use lv8_common::error::{Error, Result};
+use regex::{Captures, Regex, RegexBuilder};
+
+use super::{
+    argument, array, boolean, integer, integer_argument, null, object, string, string_argument,
+    type_error,
+};
+use crate::core::{
+    function::{call_function, InternalFunction, NativeFunction},
+    scope::ValueType,
+    PrimitiveTypes,
+};
+
+/// Patterns are either strings or the objects returned by `Regex.compile`. Match
+/// positions are character indexes, like in the `String` module.
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[
+    ("compile", compile),
+    ("escape", escape),
+    ("is_match", is_match),
+    ("find", find),
+    ("find_all", find_all),
+    ("captures", captures),
+    ("replace", replace),
+    ("replace_all", replace_all),
+    ("split", split),
+];
+
+/// Functions bound to the objects returned by `Regex.compile`, the pattern is passed
+/// as the first argument.
+const METHODS: &[(&str, NativeFunction)] = &[
+    ("is_match", is_match),
+    ("find", find),
+    ("find_all", find_all),
+    ("captures", captures),
+    ("replace", replace),
+    ("replace_all", replace_all),
+    ("split", split),
+];
+
+/// Compiled patterns are kept so using the same pattern in a loop doesn't rebuild it.
+const CACHE_SIZE: usize = 256;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<(String, String), Rc<Regex>>> = RefCell::new(HashMap::new());
+}
+
+/// Flags: `i` (case insensitive), `m` (multi line), `s` (`.` matches new lines),
+/// `x` (ignore whitespace and comments) and `U` (swap greediness).
+fn build(function: &str, pattern: &str, flags: &str) -> Result<Rc<Regex>> {
+    let key = (pattern.to_string(), flags.to_string());
+
+    if let Some(regex) = CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        return Ok(regex);
+    }
+
+    let mut builder = RegexBuilder::new(pattern);
+
+    for flag in flags.chars() {
+        match flag {
+            'i' => builder.case_insensitive(true),
+            'm' => builder.multi_line(true),
+            's' => builder.dot_matches_new_line(true),
+            'x' => builder.ignore_whitespace(true),
+            'U' => builder.swap_greed(true),
+            _ => {
+                return Err(Error::value(&format!(
+                    "{}() unknown flag {:?}",
+                    function, flag
+                )))
+            }
+        };
+    }
+
+    let regex = Rc::new(
+        builder
+            .build()
+            .map_err(|e| Error::value(&format!("{}() invalid pattern: {}", function, e)))?,
+    );
+
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+
+        if cache.len() >= CACHE_SIZE {
+            cache.clear();
+        }
+
+        cache.insert(key, Rc::clone(&regex));
+    });
+
+    Ok(regex)
+}
+
+fn pattern_argument(function: &str, args: &[ValueType], index: usize) -> Result<Rc<Regex>> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::String(pattern)) => build(function, pattern, ""),
+        ValueType::Variable(PrimitiveTypes::Object(entries)) => {
+            match (entries.get("pattern"), entries.get("flags")) {
+                (
+                    Some(ValueType::Variable(PrimitiveTypes::String(pattern))),
+                    Some(ValueType::Variable(PrimitiveTypes::String(flags))),
+                ) => build(function, pattern, flags),
+                _ => Err(type_error(function, "a pattern", args, index)),
+            }
+        }
+        _ => Err(type_error(function, "a pattern", args, index)),
+    }
+}
+
+fn char_index(text: &str, byte_index: usize) -> ValueType {
+    integer(text[..byte_index].chars().count())
+}
+
+fn match_object(text: &str, found: regex::Match) -> BTreeMap<String, ValueType> {
+    let mut entries = BTreeMap::new();
+
+    entries.insert("text".to_string(), string(found.as_str().to_string()));
+    entries.insert("start".to_string(), char_index(text, found.start()));
+    entries.insert("end".to_string(), char_index(text, found.end()));
+
+    entries
+}
+
+/// The whole match plus `groups` (positional, `null` when a group didn't take part)
+/// and `named` (an object of the named groups).
+fn captures_object(regex: &Regex, text: &str, captures: &Captures) -> ValueType {
+    let mut entries = match_object(text, captures.get(0).unwrap());
+
+    let group = |found: Option<regex::Match>| {
+        found
+            .map(|found| string(found.as_str().to_string()))
+            .unwrap_or_else(null)
+    };
+
+    entries.insert(
+        "groups".to_string(),
+        array(captures.iter().skip(1).map(group).collect()),
+    );
+    entries.insert(
+        "named".to_string(),
+        object(
+            regex
+                .capture_names()
+                .flatten()
+                .map(|name| (name.to_string(), group(captures.name(name))))
+                .collect(),
+        ),
+    );
+
+    object(entries)
+}
+
+/// `Regex.compile(pattern, flags)` checks the pattern and returns an object with
+/// `pattern`, `flags` and the functions of this module bound to it, e.g. `re.find(text)`.
+fn compile(args: Vec<ValueType>) -> Result<ValueType> {
+    let pattern = string_argument("Regex.compile", &args, 0)?;
+    let flags = if args.len() > 1 {
+        string_argument("Regex.compile", &args, 1)?
+    } else {
+        ""
+    };
+
+    build("Regex.compile", pattern, flags)?;
+
+    let mut descriptor = BTreeMap::new();
+
+    descriptor.insert("pattern".to_string(), string(pattern.to_string()));
+    descriptor.insert("flags".to_string(), string(flags.to_string()));
+
+    let mut entries = descriptor.clone();
+    let descriptor = object(descriptor);
+
+    for (name, function) in METHODS {
+        let descriptor = descriptor.clone();
+
+        entries.insert(
+            name.to_string(),
+            ValueType::InternalFunction(InternalFunction::from_closure(
+                &format!("Regex.{}", name),
+                move |mut args| {
+                    args.insert(0, descriptor.clone());
+
+                    function(args)
+                },
+            )),
+        );
+    }
+
+    Ok(object(entries))
+}
+
+fn escape(args: Vec<ValueType>) -> Result<ValueType> {
+    let text = string_argument("Regex.escape", &args, 0)?;
+
+    Ok(string(regex::escape(text)))
+}
+
+fn is_match(args: Vec<ValueType>) -> Result<ValueType> {
+    let regex = pattern_argument("Regex.is_match", &args, 0)?;
+    let text = string_argument("Regex.is_match", &args, 1)?;
+
+    Ok(boolean(regex.is_match(text)))
+}
+
+/// Returns `{text, start, end}` for the first match, or `null`.
+fn find(args: Vec<ValueType>) -> Result<ValueType> {
+    let regex = pattern_argument("Regex.find", &args, 0)?;
+    let text = string_argument("Regex.find", &args, 1)?;
+
+    Ok(regex
+        .find(text)
+        .map(|found| object(match_object(text, found)))
+        .unwrap_or_else(null))
+}
+
+fn find_all(args: Vec<ValueType>) -> Result<ValueType> {
+    let regex = pattern_argument("Regex.find_all", &args, 0)?;
+    let text = string_argument("Regex.find_all", &args, 1)?;
+
+    Ok(array(
+        regex
+            .find_iter(text)
+            .map(|found| object(match_object(text, found)))
+            .collect(),
+    ))
+}
+
+fn captures(args: Vec<ValueType>) -> Result<ValueType> {
+    let regex = pattern_argument("Regex.captures", &args, 0)?;
+    let text = string_argument("Regex.captures", &args, 1)?;
+
+    Ok(regex
+        .captures(text)
+        .map(|captures| captures_object(&regex, text, &captures))
+        .unwrap_or_else(null))
+}
+
+/// The replacement is either a string, where `$1` and `${name}` refer to groups, or a
+/// function called with the captures object of every match.
+fn replace_matches(function: &str, args: &[ValueType], limit: usize) -> Result<ValueType> {
+    let regex = pattern_argument(function, args, 0)?;
+    let text = string_argument(function, args, 1)?;
+
+    match argument(function, args, 2)? {
+        ValueType::Variable(PrimitiveTypes::String(replacement)) => Ok(string(
+            regex
+                .replacen(text, limit, replacement.as_str())
+                .to_string(),
+        )),
+        callback @ (ValueType::Function(_) | ValueType::InternalFunction(_)) => {
+            let mut result = String::new();
+            let mut last = 0;
+
+            for captures in regex.captures_iter(text) {
+                let whole = captures.get(0).unwrap();
+                let replacement = call_function(
+                    callback.clone(),
+                    vec![captures_object(&regex, text, &captures)],
+                )?;
+
+                result.push_str(&text[last..whole.start()]);
+                result.push_str(&replacement.to_string());
+                last = whole.end();
+
+                if limit == 1 {
+                    break;
+                }
+            }
+
+            result.push_str(&text[last..]);
+
+            Ok(string(result))
+        }
+        _ => Err(type_error(function, "a string or a function", args, 2)),
+    }
+}
+
+fn replace(args: Vec<ValueType>) -> Result<ValueType> {
+    replace_matches("Regex.replace", &args, 1)
+}
+
+fn replace_all(args: Vec<ValueType>) -> Result<ValueType> {
+    replace_matches("Regex.replace_all", &args, 0)
+}
+
+/// `Regex.split(pattern, text, limit)`, at most `limit` pieces are returned when given.
+fn split(args: Vec<ValueType>) -> Result<ValueType> {
+    let regex = pattern_argument("Regex.split", &args, 0)?;
+    let text = string_argument("Regex.split", &args, 1)?;
+
+    let pieces: Vec<ValueType> = if args.len() > 2 {
+        let limit = integer_argument("Regex.split", &args, 2)?;
+
+        regex
+            .splitn(text, limit.max(0) as usize)
+            .map(|piece| string(piece.to_string()))
+            .collect()
+    } else {
+        regex
+            .split(text)
+            .map(|piece| string(piece.to_string()))
+            .collect()
+    };
+
+    Ok(array(pieces))
+}
@@ -0,0 +1,241 @@
+use lv8_common::error::Result;
+
+use super::{
+    argument, array, array_argument, boolean, clamp_index, integer, integer_argument, number,
+    string, string_argument, too_long_error, type_error, MAX_STRING_LENGTH,
+};
+use crate::core::{function::NativeFunction, scope::ValueType, PrimitiveTypes};
+use lv8_parser::Either;
+
+/// Also used for method calls on string values, the string is passed as the first argument.
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[
+    ("len", len),
+    ("upper", upper),
+    ("lower", lower),
+    ("trim", trim),
+    ("trim_start", trim_start),
+    ("trim_end", trim_end),
+    ("split", split),
+    ("join", join),
+    ("replace", replace),
+    ("contains", contains),
+    ("starts_with", starts_with),
+    ("ends_with", ends_with),
+    ("find", find),
+    ("repeat", repeat),
+    ("pad_left", pad_left),
+    ("pad_right", pad_right),
+    ("chars", chars),
+    ("reverse", reverse),
+    ("slice", slice),
+    ("format", super::format::format),
+];
+
+fn len(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.len", &args, 0)?;
+
+    Ok(integer(value.chars().count()))
+}
+
+fn upper(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.upper", &args, 0)?;
+
+    Ok(string(value.to_uppercase()))
+}
+
+fn lower(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.lower", &args, 0)?;
+
+    Ok(string(value.to_lowercase()))
+}
+
+fn trim(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.trim", &args, 0)?;
+
+    Ok(string(value.trim().to_string()))
+}
+
+fn trim_start(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.trim_start", &args, 0)?;
+
+    Ok(string(value.trim_start().to_string()))
+}
+
+fn trim_end(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.trim_end", &args, 0)?;
+
+    Ok(string(value.trim_end().to_string()))
+}
+
+fn split(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.split", &args, 0)?;
+
+    let parts: Vec<&str> = if args.len() > 1 {
+        match string_argument("String.split", &args, 1)? {
+            "" => value.split_terminator("").skip(1).collect(),
+            separator => value.split(separator).collect(),
+        }
+    } else {
+        value.split_whitespace().collect()
+    };
+
+    Ok(array(
+        parts
+            .into_iter()
+            .map(|part| string(part.to_string()))
+            .collect(),
+    ))
+}
+
+/// Accepts both `String.join(parts, separator)` and `separator.join(parts)`.
+pub(super) fn join(args: Vec<ValueType>) -> Result<ValueType> {
+    let (parts, separator) = match argument("String.join", &args, 0)? {
+        ValueType::Variable(PrimitiveTypes::String(separator)) => {
+            (array_argument("String.join", &args, 1)?, separator.as_str())
+        }
+        _ => (
+            array_argument("String.join", &args, 0)?,
+            if args.len() > 1 {
+                string_argument("String.join", &args, 1)?
+            } else {
+                ""
+            },
+        ),
+    };
+
+    Ok(string(
+        parts
+            .iter()
+            .map(|part| part.to_string())
+            .collect::<Vec<String>>()
+            .join(separator),
+    ))
+}
+
+fn replace(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.replace", &args, 0)?;
+    let from = string_argument("String.replace", &args, 1)?;
+    let to = string_argument("String.replace", &args, 2)?;
+
+    if args.len() > 3 {
+        let count = integer_argument("String.replace", &args, 3)?;
+
+        return Ok(string(value.replacen(from, to, count.max(0) as usize)));
+    }
+
+    Ok(string(value.replace(from, to)))
+}
+
+fn contains(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.contains", &args, 0)?;
+    let pattern = string_argument("String.contains", &args, 1)?;
+
+    Ok(boolean(value.contains(pattern)))
+}
+
+fn starts_with(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.starts_with", &args, 0)?;
+    let pattern = string_argument("String.starts_with", &args, 1)?;
+
+    Ok(boolean(value.starts_with(pattern)))
+}
+
+fn ends_with(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.ends_with", &args, 0)?;
+    let pattern = string_argument("String.ends_with", &args, 1)?;
+
+    Ok(boolean(value.ends_with(pattern)))
+}
+
+/// Returns the character index of the first match or `-1`.
+fn find(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.find", &args, 0)?;
+    let pattern = string_argument("String.find", &args, 1)?;
+
+    match value.find(pattern) {
+        Some(offset) => Ok(integer(value[..offset].chars().count())),
+        None => Ok(number(Either::Left(-1))),
+    }
+}
+
+fn repeat(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.repeat", &args, 0)?;
+    let count = integer_argument("String.repeat", &args, 1)?.max(0) as usize;
+
+    match value.len().checked_mul(count) {
+        Some(length) if length <= MAX_STRING_LENGTH => Ok(string(value.repeat(count))),
+        _ => Err(too_long_error("String.repeat")),
+    }
+}
+
+fn padding(function: &str, args: &[ValueType]) -> Result<(String, String)> {
+    let value = string_argument(function, args, 0)?;
+    let width = integer_argument(function, args, 1)?.max(0) as usize;
+    let fill = if args.len() > 2 {
+        string_argument(function, args, 2)?
+    } else {
+        " "
+    };
+
+    if fill.is_empty() {
+        return Err(type_error(function, "a non empty fill string", args, 2));
+    }
+
+    if width > MAX_STRING_LENGTH {
+        return Err(too_long_error(function));
+    }
+
+    let missing = width.saturating_sub(value.chars().count());
+
+    Ok((
+        value.to_string(),
+        fill.chars().cycle().take(missing).collect(),
+    ))
+}
+
+fn pad_left(args: Vec<ValueType>) -> Result<ValueType> {
+    let (value, padding) = padding("String.pad_left", &args)?;
+
+    Ok(string(padding + &value))
+}
+
+fn pad_right(args: Vec<ValueType>) -> Result<ValueType> {
+    let (value, padding) = padding("String.pad_right", &args)?;
+
+    Ok(string(value + &padding))
+}
+
+fn chars(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.chars", &args, 0)?;
+
+    Ok(array(
+        value.chars().map(|char| string(char.to_string())).collect(),
+    ))
+}
+
+fn reverse(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.reverse", &args, 0)?;
+
+    Ok(string(value.chars().rev().collect()))
+}
+
+/// `String.slice(value, start, end)` by character, `end` defaults to the end of the string.
+fn slice(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = string_argument("String.slice", &args, 0)?;
+    let len = value.chars().count();
+
+    let start = clamp_index(integer_argument("String.slice", &args, 1)?, len);
+    let end = if args.len() > 2 {
+        clamp_index(integer_argument("String.slice", &args, 2)?, len)
+    } else {
+        len
+    };
+
+    if start >= end {
+        return Ok(string(String::new()));
+    }
+
+    Ok(string(
+        value.chars().skip(start).take(end - start).collect(),
+    ))
+}
@@ -0,0 +1,450 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeDelta, Timelike, Utc,
+};
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{
+    argument, as_f64, integer, number, number_argument, object, object_argument, string,
+    string_argument, type_error, undefined,
+};
+use crate::core::{function::NativeFunction, scope::ValueType, PrimitiveTypes};
+
+/// Durations are numbers of seconds, or objects like `{hours: 1, minutes: 30}`.
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[
+    ("now", now),
+    ("monotonic", monotonic),
+    ("sleep", sleep),
+    ("utc", utc),
+    ("local", local),
+    ("datetime", datetime),
+    ("from_unix", from_unix),
+    ("parse", parse),
+    ("format", format),
+    ("unix", unix),
+    ("add", add),
+    ("diff", diff),
+    ("to_utc", to_utc),
+    ("to_offset", to_offset),
+    ("parts", parts),
+];
+
+/// Also used for method calls on datetime values, the datetime is passed as the first
+/// argument.
+pub const METHODS: &[(&str, NativeFunction)] = &[
+    ("format", format),
+    ("unix", unix),
+    ("add", add),
+    ("diff", diff),
+    ("to_utc", to_utc),
+    ("to_offset", to_offset),
+    ("parts", parts),
+];
+
+fn datetime_value(value: DateTime<FixedOffset>) -> ValueType {
+    ValueType::Variable(PrimitiveTypes::DateTime(value))
+}
+
+fn float(value: f64) -> ValueType {
+    number(Either::Right(value))
+}
+
+fn datetime_argument(
+    function: &str,
+    args: &[ValueType],
+    index: usize,
+) -> Result<DateTime<FixedOffset>> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::DateTime(value)) => Ok(*value),
+        _ => Err(type_error(function, "a datetime", args, index)),
+    }
+}
+
+fn utc_offset() -> FixedOffset {
+    FixedOffset::east_opt(0).unwrap()
+}
+
+/// Accepts `"Z"`, `"UTC"`, `"+05:30"`, `"-0800"` and `"+02"`.
+fn parse_offset(function: &str, offset: &str) -> Result<FixedOffset> {
+    let invalid = || Error::value(&format!("{}() invalid offset {:?}", function, offset));
+
+    if offset == "Z" || offset.eq_ignore_ascii_case("utc") {
+        return Ok(utc_offset());
+    }
+
+    let (sign, rest) = match (offset.get(..1), offset.get(1..)) {
+        (Some("+"), Some(rest)) => (1, rest),
+        (Some("-"), Some(rest)) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+
+    // `HH`, `HHMM` or `HH:MM`, nothing else.
+    let bytes = rest.as_bytes();
+    let valid = match bytes.len() {
+        2 | 4 => bytes.iter().all(u8::is_ascii_digit),
+        5 => bytes.iter().enumerate().all(|(index, byte)| {
+            if index == 2 {
+                *byte == b':'
+            } else {
+                byte.is_ascii_digit()
+            }
+        }),
+        _ => false,
+    };
+
+    if !valid {
+        return Err(invalid());
+    }
+
+    let hours: i32 = rest[..2].parse().map_err(|_| invalid())?;
+    let minutes: i32 = match rest.len() {
+        2 => 0,
+        length => rest[length - 2..].parse().map_err(|_| invalid())?,
+    };
+
+    if minutes >= 60 {
+        return Err(invalid());
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+fn out_of_range(function: &str) -> Error {
+    Error::value(&format!("{}() datetime out of range", function))
+}
+
+fn seconds_to_delta(function: &str, seconds: f64) -> Result<TimeDelta> {
+    if !seconds.is_finite() {
+        return Err(Error::value(&format!(
+            "{}() invalid duration {}",
+            function, seconds
+        )));
+    }
+
+    if seconds.abs() >= i64::MAX as f64 {
+        return Err(out_of_range(function));
+    }
+
+    TimeDelta::try_seconds(seconds.trunc() as i64)
+        .map(|delta| delta + TimeDelta::nanoseconds((seconds.fract() * 1e9) as i64))
+        .ok_or_else(|| out_of_range(function))
+}
+
+fn duration_argument(function: &str, args: &[ValueType], index: usize) -> Result<TimeDelta> {
+    match argument(function, args, index)? {
+        ValueType::Variable(PrimitiveTypes::Number(seconds)) => {
+            seconds_to_delta(function, as_f64(seconds))
+        }
+        ValueType::Variable(PrimitiveTypes::Object(entries)) => {
+            let mut seconds = 0.0;
+
+            for (unit, value) in entries {
+                let scale = match unit.as_str() {
+                    "weeks" => 604800.0,
+                    "days" => 86400.0,
+                    "hours" => 3600.0,
+                    "minutes" => 60.0,
+                    "seconds" => 1.0,
+                    "milliseconds" => 0.001,
+                    _ => {
+                        return Err(Error::value(&format!(
+                            "{}() unknown duration unit {:?}",
+                            function, unit
+                        )))
+                    }
+                };
+
+                match value {
+                    ValueType::Variable(PrimitiveTypes::Number(value)) => {
+                        seconds += as_f64(value) * scale
+                    }
+                    value => {
+                        return Err(Error::r#type(&format!(
+                            "{}() expects a number for {:?}, got {}",
+                            function,
+                            unit,
+                            value.type_name()
+                        )))
+                    }
+                }
+            }
+
+            seconds_to_delta(function, seconds)
+        }
+        _ => Err(type_error(function, "a duration", args, index)),
+    }
+}
+
+fn delta_to_seconds(delta: TimeDelta) -> f64 {
+    delta.num_seconds() as f64 + delta.subsec_nanos() as f64 / 1e9
+}
+
+/// The current unix timestamp in seconds.
+fn now(_args: Vec<ValueType>) -> Result<ValueType> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    Ok(float(elapsed.as_secs_f64()))
+}
+
+/// Seconds from an arbitrary starting point that never goes backwards, for measuring
+/// elapsed time.
+fn monotonic(_args: Vec<ValueType>) -> Result<ValueType> {
+    static START: OnceLock<Instant> = OnceLock::new();
+
+    Ok(float(
+        START.get_or_init(Instant::now).elapsed().as_secs_f64(),
+    ))
+}
+
+/// `Time.sleep(ms)`
+fn sleep(args: Vec<ValueType>) -> Result<ValueType> {
+    let milliseconds = as_f64(&number_argument("Time.sleep", &args, 0)?);
+
+    let duration = Duration::try_from_secs_f64(milliseconds / 1000.0)
+        .map_err(|_| Error::value(&format!("Time.sleep() invalid duration {}", milliseconds)))?;
+
+    thread::sleep(duration);
+
+    Ok(undefined())
+}
+
+fn utc(_args: Vec<ValueType>) -> Result<ValueType> {
+    Ok(datetime_value(Utc::now().fixed_offset()))
+}
+
+/// The current time at the offset of the local timezone.
+fn local(_args: Vec<ValueType>) -> Result<ValueType> {
+    Ok(datetime_value(Local::now().fixed_offset()))
+}
+
+/// `Time.datetime({year, month, day, hour, minute, second, millisecond, offset})`, only
+/// `year` is required, the object returned by `Time.parts` is accepted as well.
+fn datetime(args: Vec<ValueType>) -> Result<ValueType> {
+    let entries = object_argument("Time.datetime", &args, 0)?;
+
+    let field = |name: &str, default: i64| -> Result<i64> {
+        match entries.get(name) {
+            None => Ok(default),
+            Some(ValueType::Variable(PrimitiveTypes::Number(Either::Left(value)))) => {
+                Ok(*value as i64)
+            }
+            Some(value) => Err(Error::r#type(&format!(
+                "Time.datetime() expects an integer for {:?}, got {}",
+                name,
+                value.type_name()
+            ))),
+        }
+    };
+
+    if !entries.contains_key("year") {
+        return Err(Error::r#type("Time.datetime() missing \"year\""));
+    }
+
+    let offset = match entries.get("offset") {
+        None => utc_offset(),
+        Some(ValueType::Variable(PrimitiveTypes::String(offset))) => {
+            parse_offset("Time.datetime", offset)?
+        }
+        Some(value) => {
+            return Err(Error::r#type(&format!(
+                "Time.datetime() expects a string for \"offset\", got {}",
+                value.type_name()
+            )))
+        }
+    };
+
+    let invalid = || Error::value("Time.datetime() invalid date or time");
+    let part = |name: &str, default: i64| -> Result<u32> {
+        u32::try_from(field(name, default)?).map_err(|_| invalid())
+    };
+
+    let year = i32::try_from(field("year", 0)?).map_err(|_| invalid())?;
+    let (month, day) = (part("month", 1)?, part("day", 1)?);
+    let (hour, minute, second) = (part("hour", 0)?, part("minute", 0)?, part("second", 0)?);
+    let millisecond = part("millisecond", 0)?;
+
+    let naive = NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_milli_opt(hour, minute, second, millisecond))
+        .ok_or_else(invalid)?;
+
+    naive
+        .and_local_timezone(offset)
+        .single()
+        .map(datetime_value)
+        .ok_or_else(invalid)
+}
+
+/// `Time.from_unix(seconds, offset)`, in UTC unless an offset is given.
+fn from_unix(args: Vec<ValueType>) -> Result<ValueType> {
+    let seconds = as_f64(&number_argument("Time.from_unix", &args, 0)?);
+    let offset = if args.len() > 1 {
+        parse_offset(
+            "Time.from_unix",
+            string_argument("Time.from_unix", &args, 1)?,
+        )?
+    } else {
+        utc_offset()
+    };
+
+    let delta = seconds_to_delta("Time.from_unix", seconds)?;
+
+    DateTime::UNIX_EPOCH
+        .checked_add_signed(delta)
+        .map(|value| datetime_value(value.with_timezone(&offset)))
+        .ok_or_else(|| out_of_range("Time.from_unix"))
+}
+
+fn invalid_datetime(function: &str, text: &str) -> Error {
+    Error::value(&format!("{}() invalid datetime {:?}", function, text))
+}
+
+/// `Time.parse(text, format)` parses ISO-8601 by default, or a strftime-style pattern.
+/// Datetimes without an offset are taken as UTC, and dates as midnight.
+fn parse(args: Vec<ValueType>) -> Result<ValueType> {
+    let text = string_argument("Time.parse", &args, 0)?;
+
+    let parsed = if args.len() > 1 {
+        let pattern = string_argument("Time.parse", &args, 1)?;
+
+        DateTime::parse_from_str(text, pattern).ok().or_else(|| {
+            NaiveDateTime::parse_from_str(text, pattern)
+                .or_else(|_| {
+                    NaiveDate::parse_from_str(text, pattern)
+                        .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+                })
+                .ok()
+                .map(|naive| naive.and_utc().fixed_offset())
+        })
+    } else {
+        DateTime::parse_from_rfc3339(text).ok().or_else(|| {
+            [
+                "%Y-%m-%dT%H:%M:%S%.f",
+                "%Y-%m-%d %H:%M:%S%.f",
+                "%Y-%m-%dT%H:%M",
+            ]
+            .iter()
+            .find_map(|pattern| NaiveDateTime::parse_from_str(text, pattern).ok())
+            .or_else(|| {
+                NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+            })
+            .map(|naive| naive.and_utc().fixed_offset())
+        })
+    };
+
+    parsed
+        .map(datetime_value)
+        .ok_or_else(|| invalid_datetime("Time.parse", text))
+}
+
+/// `Time.format(datetime, pattern)` formats as ISO-8601 unless a strftime-style pattern
+/// like `"%Y-%m-%d %H:%M"` is given.
+fn format(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = datetime_argument("Time.format", &args, 0)?;
+
+    if args.len() < 2 {
+        return Ok(string(datetime_value(value).to_string()));
+    }
+
+    let pattern = string_argument("Time.format", &args, 1)?;
+    let items: Vec<Item> = StrftimeItems::new(pattern).collect();
+
+    let invalid = || Error::value(&format!("Time.format() invalid pattern {:?}", pattern));
+
+    if items.contains(&Item::Error) {
+        return Err(invalid());
+    }
+
+    let mut result = String::new();
+
+    write!(result, "{}", value.format_with_items(items.iter())).map_err(|_| invalid())?;
+
+    Ok(string(result))
+}
+
+/// The unix timestamp of a datetime in seconds.
+fn unix(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = datetime_argument("Time.unix", &args, 0)?;
+
+    Ok(float(delta_to_seconds(
+        value.to_utc() - DateTime::UNIX_EPOCH,
+    )))
+}
+
+/// `Time.add(datetime, duration)`, negative durations go back in time.
+fn add(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = datetime_argument("Time.add", &args, 0)?;
+    let delta = duration_argument("Time.add", &args, 1)?;
+
+    value
+        .checked_add_signed(delta)
+        .map(datetime_value)
+        .ok_or_else(|| out_of_range("Time.add"))
+}
+
+/// `Time.diff(a, b)` is the number of seconds from `b` to `a`.
+fn diff(args: Vec<ValueType>) -> Result<ValueType> {
+    let left = datetime_argument("Time.diff", &args, 0)?;
+    let right = datetime_argument("Time.diff", &args, 1)?;
+
+    Ok(float(delta_to_seconds(left - right)))
+}
+
+fn to_utc(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = datetime_argument("Time.to_utc", &args, 0)?;
+
+    Ok(datetime_value(value.with_timezone(&utc_offset())))
+}
+
+/// `Time.to_offset(datetime, offset)` is the same instant seen from another offset.
+fn to_offset(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = datetime_argument("Time.to_offset", &args, 0)?;
+    let offset = parse_offset(
+        "Time.to_offset",
+        string_argument("Time.to_offset", &args, 1)?,
+    )?;
+
+    Ok(datetime_value(value.with_timezone(&offset)))
+}
+
+/// Returns `{year, month, day, hour, minute, second, millisecond, weekday, ordinal,
+/// offset}`, `weekday` goes from 1 (Monday) to 7 (Sunday).
+fn parts(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = datetime_argument("Time.parts", &args, 0)?;
+
+    let mut entries = BTreeMap::new();
+
+    entries.insert(
+        "year".to_string(),
+        number(Either::Left(value.year() as isize)),
+    );
+    entries.insert("month".to_string(), integer(value.month() as usize));
+    entries.insert("day".to_string(), integer(value.day() as usize));
+    entries.insert("hour".to_string(), integer(value.hour() as usize));
+    entries.insert("minute".to_string(), integer(value.minute() as usize));
+    entries.insert("second".to_string(), integer(value.second() as usize));
+    entries.insert(
+        "millisecond".to_string(),
+        integer((value.nanosecond() / 1_000_000) as usize),
+    );
+    entries.insert(
+        "weekday".to_string(),
+        integer(value.weekday().number_from_monday() as usize),
+    );
+    entries.insert("ordinal".to_string(), integer(value.ordinal() as usize));
+    entries.insert("offset".to_string(), string(value.offset().to_string()));
+
+    Ok(object(entries))
+}
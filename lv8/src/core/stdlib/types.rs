@@ -0,0 +1,289 @@
+use lv8_common::error::{Error, Result};
+use lv8_parser::Either;
+
+use super::{argument, boolean, integer_argument, number, string};
+use crate::core::{
+    expression::value_to_bool, function::NativeFunction, scope::ValueType, PrimitiveTypes,
+};
+
+/// Installed as global functions.
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[
+    ("type_of", type_of),
+    ("to_number", to_number),
+    ("to_int", to_int),
+    ("to_float", to_float),
+    ("to_string", to_string),
+    ("to_bool", to_bool),
+    ("is_null", is_null),
+    ("is_undefined", is_undefined),
+    ("is_bool", is_bool),
+    ("is_number", is_number),
+    ("is_int", is_int),
+    ("is_float", is_float),
+    ("is_string", is_string),
+    ("is_array", is_array),
+    ("is_object", is_object),
+    ("is_datetime", is_datetime),
+    ("is_function", is_function),
+    ("is_module", is_module),
+];
+
+fn invalid(function: &str, value: &str, kind: &str) -> Error {
+    Error::value(&format!(
+        "{}() invalid {} literal {:?}",
+        function, kind, value
+    ))
+}
+
+fn parse_integer(function: &str, value: &str, base: u32) -> Result<isize> {
+    let trimmed = value.trim().replace('_', "");
+
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(&trimmed)),
+    };
+
+    let prefix = match base {
+        2 => Some(["0b", "0B"]),
+        8 => Some(["0o", "0O"]),
+        16 => Some(["0x", "0X"]),
+        _ => None,
+    };
+
+    let digits = prefix
+        .and_then(|prefixes| {
+            prefixes
+                .iter()
+                .find_map(|prefix| digits.strip_prefix(prefix))
+        })
+        .unwrap_or(digits);
+
+    let sign = if negative { "-" } else { "" };
+
+    isize::from_str_radix(&format!("{}{}", sign, digits), base)
+        .map_err(|_| invalid(function, value, &format!("base {} integer", base)))
+}
+
+fn parse_float(function: &str, value: &str) -> Result<f64> {
+    let trimmed = value.trim();
+
+    match trimmed.to_lowercase().as_str() {
+        "inf" | "+inf" | "infinity" => Ok(f64::INFINITY),
+        "-inf" | "-infinity" => Ok(f64::NEG_INFINITY),
+        "nan" => Ok(f64::NAN),
+        _ => trimmed
+            .replace('_', "")
+            .parse()
+            .map_err(|_| invalid(function, value, "float")),
+    }
+}
+
+fn conversion_error(function: &str, value: &ValueType) -> Error {
+    Error::r#type(&format!(
+        "{}() cannot convert {} to a number",
+        function,
+        value.type_name()
+    ))
+}
+
+fn type_of(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("type_of", &args, 0)?;
+
+    Ok(string(value.type_name().to_string()))
+}
+
+/// Converts to an integer when the value looks like one, otherwise to a float.
+fn to_number(args: Vec<ValueType>) -> Result<ValueType> {
+    match argument("to_number", &args, 0)? {
+        ValueType::Variable(PrimitiveTypes::Number(value)) => Ok(number(value.clone())),
+        ValueType::Variable(PrimitiveTypes::Boolean(value)) => {
+            Ok(number(Either::Left(*value as isize)))
+        }
+        ValueType::Variable(PrimitiveTypes::String(value)) => {
+            match parse_integer("to_number", value, 10) {
+                Ok(integer) => Ok(number(Either::Left(integer))),
+                Err(_) => Ok(number(Either::Right(parse_float("to_number", value)?))),
+            }
+        }
+        value => Err(conversion_error("to_number", value)),
+    }
+}
+
+/// `to_int(value, base)`, floats are truncated and strings are parsed in `base`
+/// (10 by default, `0x`, `0o` and `0b` prefixes are accepted for their bases).
+fn to_int(args: Vec<ValueType>) -> Result<ValueType> {
+    let base = if args.len() > 1 {
+        integer_argument("to_int", &args, 1)?
+    } else {
+        10
+    };
+
+    if !(2..=36).contains(&base) {
+        return Err(Error::value(&format!(
+            "to_int() base must be between 2 and 36, got {}",
+            base
+        )));
+    }
+
+    match argument("to_int", &args, 0)? {
+        ValueType::Variable(PrimitiveTypes::Number(Either::Left(value))) => {
+            Ok(number(Either::Left(*value)))
+        }
+        ValueType::Variable(PrimitiveTypes::Number(Either::Right(value))) => {
+            // `isize::MAX as f64` rounds up to 2^63, which is already out of range.
+            let truncated = value.trunc();
+            if !value.is_finite() || truncated < isize::MIN as f64 || truncated >= isize::MAX as f64
+            {
+                return Err(Error::value(&format!(
+                    "to_int() cannot convert {} to an integer",
+                    value
+                )));
+            }
+
+            Ok(number(Either::Left(truncated as isize)))
+        }
+        ValueType::Variable(PrimitiveTypes::Boolean(value)) => {
+            Ok(number(Either::Left(*value as isize)))
+        }
+        ValueType::Variable(PrimitiveTypes::String(value)) => Ok(number(Either::Left(
+            parse_integer("to_int", value, base as u32)?,
+        ))),
+        value => Err(conversion_error("to_int", value)),
+    }
+}
+
+fn to_float(args: Vec<ValueType>) -> Result<ValueType> {
+    match argument("to_float", &args, 0)? {
+        ValueType::Variable(PrimitiveTypes::Number(Either::Left(value))) => {
+            Ok(number(Either::Right(*value as f64)))
+        }
+        ValueType::Variable(PrimitiveTypes::Number(Either::Right(value))) => {
+            Ok(number(Either::Right(*value)))
+        }
+        ValueType::Variable(PrimitiveTypes::Boolean(value)) => {
+            Ok(number(Either::Right(*value as isize as f64)))
+        }
+        ValueType::Variable(PrimitiveTypes::String(value)) => {
+            Ok(number(Either::Right(parse_float("to_float", value)?)))
+        }
+        value => Err(conversion_error("to_float", value)),
+    }
+}
+
+fn to_string(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("to_string", &args, 0)?;
+
+    Ok(string(value.to_string()))
+}
+
+fn to_bool(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("to_bool", &args, 0)?;
+
+    Ok(boolean(value_to_bool(value.clone())))
+}
+
+fn is_null(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_null", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Null)
+    )))
+}
+
+fn is_undefined(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_undefined", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Undefined)
+    )))
+}
+
+fn is_bool(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_bool", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Boolean(_))
+    )))
+}
+
+fn is_number(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_number", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Number(_))
+    )))
+}
+
+fn is_int(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_int", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Number(Either::Left(_)))
+    )))
+}
+
+fn is_float(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_float", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Number(Either::Right(_)))
+    )))
+}
+
+fn is_string(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_string", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::String(_))
+    )))
+}
+
+fn is_array(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_array", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Array(_))
+    )))
+}
+
+fn is_object(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_object", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::Object(_))
+    )))
+}
+
+fn is_datetime(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_datetime", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Variable(PrimitiveTypes::DateTime(_))
+    )))
+}
+
+/// True for both user defined and internal functions.
+fn is_function(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_function", &args, 0)?;
+
+    Ok(boolean(matches!(
+        value,
+        ValueType::Function(_) | ValueType::InternalFunction(_)
+    )))
+}
+
+fn is_module(args: Vec<ValueType>) -> Result<ValueType> {
+    let value = argument("is_module", &args, 0)?;
+
+    Ok(boolean(matches!(value, ValueType::Module(_))))
+}
@@ -2,24 +2,266 @@ mod core;
 mod read;
 mod repl;
 
-use clap::Parser;
-use core::execute_file;
-use lv8_common::error::Result;
+use clap::{Parser, Subcommand, ValueEnum};
+use core::{
+    capabilities::{Capabilities, PathAccess},
+    execute_file, execute_source, Metadata,
+};
+use lv8_common::error::{
+    self, set_color_choice, set_error_format, ColorChoice, Error, ErrorFormat, ErrorKind,
+};
+use std::path::PathBuf;
+
+/// Exit statuses of errors, from `sysexits.h`. `OS.exit(n)` exits with `n`.
+const SYNTAX_ERROR: i32 = 65;
+const MISSING_FILE: i32 = 66;
+const RUNTIME_ERROR: i32 = 70;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Script to run, `-` reads it from stdin. Without a script the REPL starts
     path: Option<String>,
+
+    /// Arguments passed to the script as `argv`
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    argv: Vec<String>,
+
+    /// Run CODE instead of a script, the arguments after it are passed as `argv`
+    #[arg(short, long, value_name = "CODE")]
+    eval: Option<String>,
+
+    /// When to color error messages, `auto` colors a terminal unless `NO_COLOR` is set
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = Color::Auto, global = true)]
+    color: Color,
+
+    /// How errors are printed to stderr, `json` prints one object per error
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = Format::Human, global = true)]
+    error_format: Format,
+
+    /// Seed for the `Random` module, to reproduce a run
+    #[arg(long, value_name = "N", global = true)]
+    seed: Option<u64>,
+
+    /// Deny everything except writing to stdout, use the `--allow-*` flags to grant more
+    #[arg(long, global = true)]
+    sandbox: bool,
+
+    /// Allow reading from stdin
+    #[arg(long, requires = "sandbox", global = true)]
+    allow_stdin: bool,
+
+    /// Allow reading environment variables
+    #[arg(long, requires = "sandbox", global = true)]
+    allow_env: bool,
+
+    /// Allow spawning processes
+    #[arg(long, requires = "sandbox", global = true)]
+    allow_run: bool,
+
+    /// Allow reading files inside DIR
+    #[arg(long, value_name = "DIR", requires = "sandbox", global = true)]
+    allow_read: Vec<PathBuf>,
+
+    /// Allow writing files inside DIR
+    #[arg(long, value_name = "DIR", requires = "sandbox", global = true)]
+    allow_write: Vec<PathBuf>,
+
+    /// Allow importing files inside DIR
+    #[arg(long, value_name = "DIR", requires = "sandbox", global = true)]
+    allow_import: Vec<PathBuf>,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run a script, `-` reads it from stdin
+    Run {
+        path: String,
+
+        /// Arguments passed to the script as `argv`
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        argv: Vec<String>,
+    },
+    /// Report the syntax errors of scripts without running them
+    Check {
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+}
 
-    if let Some(path) = args.path {
-        execute_file(path)?;
-    } else {
-        repl::run().unwrap();
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<Color> for ColorChoice {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Auto => ColorChoice::Auto,
+            Color::Always => ColorChoice::Always,
+            Color::Never => ColorChoice::Never,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Human,
+    Json,
+}
+
+impl From<Format> for ErrorFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Human => ErrorFormat::Human,
+            Format::Json => ErrorFormat::Json,
+        }
+    }
+}
+
+impl Args {
+    fn metadata(&self, argv: Vec<String>) -> Metadata {
+        Metadata::new(
+            std::env::current_dir()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string(),
+            self.capabilities(),
+            argv,
+            self.seed,
+        )
     }
 
-    Ok(())
+    fn capabilities(&self) -> Capabilities {
+        if !self.sandbox {
+            return Capabilities::all();
+        }
+
+        let mut capabilities = Capabilities::none();
+
+        capabilities.stdout = true;
+        capabilities.stdin = self.allow_stdin;
+        capabilities.env = self.allow_env;
+        capabilities.process = self.allow_run;
+
+        if !self.allow_read.is_empty() {
+            capabilities.fs_read = PathAccess::within(&self.allow_read);
+        }
+
+        if !self.allow_write.is_empty() {
+            capabilities.fs_write = PathAccess::within(&self.allow_write);
+        }
+
+        if !self.allow_import.is_empty() {
+            capabilities.imports = PathAccess::within(&self.allow_import);
+        }
+
+        capabilities
+    }
+}
+
+/// Runtime errors use 70 rather than 1, so they can be told apart from `OS.exit(1)`.
+fn exit_code(error: &Error) -> i32 {
+    match error.kind {
+        ErrorKind::Exit(code) => code,
+        ErrorKind::Syntax(_) => SYNTAX_ERROR,
+        // Raised before any frame was entered, the script itself could not be read.
+        ErrorKind::Io if error.traceback.is_empty() => MISSING_FILE,
+        _ => RUNTIME_ERROR,
+    }
+}
+
+fn main() {
+    let mut args = Args::parse();
+
+    set_color_choice(args.color.into());
+    set_error_format(args.error_format.into());
+
+    match args.command.take() {
+        Some(Command::Run { path, argv }) => run(&args, &path, argv),
+        Some(Command::Check { paths }) => check(&paths),
+        None => match (args.eval.take(), args.path.take()) {
+            // With `-e` there is no script path, every argument is for the code.
+            (Some(code), path) => {
+                let argv = path.into_iter().chain(args.argv.drain(..)).collect();
+                let metadata = args.metadata(argv);
+
+                finish(execute_source("<eval>", &code, &metadata), &metadata);
+            }
+            (None, Some(path)) => {
+                let argv = std::mem::take(&mut args.argv);
+
+                run(&args, &path, argv);
+            }
+            (None, None) => repl::run(args.metadata(Vec::new())).unwrap(),
+        },
+    }
+}
+
+/// Runs the script at `path`, or the one piped to stdin when `path` is `-`.
+fn run(args: &Args, path: &str, argv: Vec<String>) {
+    let metadata = args.metadata(argv);
+
+    let result = match path {
+        "-" => read::read_stdin().and_then(|input| execute_source("<stdin>", &input, &metadata)),
+        _ => execute_file(path, &metadata),
+    };
+
+    finish(result, &metadata);
+}
+
+/// Runs the exit handlers, also when the script failed. The error of the script decides
+/// the exit status, an error of a handler is reported after it.
+fn finish<T>(result: error::Result<T>, metadata: &Metadata) {
+    let handlers = metadata.run_exit_handlers();
+
+    match (result, handlers) {
+        (Err(e), Err(handler_error)) => {
+            report(&e);
+            report(&handler_error);
+
+            std::process::exit(exit_code(&e));
+        }
+        (result, handlers) => exit_on_error(result.and(handlers)),
+    }
+}
+
+/// Parses every script, all of their syntax errors are reported.
+fn check(paths: &[String]) {
+    let mut status = 0;
+
+    for path in paths {
+        let result = match path.as_str() {
+            "-" => read::read_stdin().and_then(|input| read::parse("<stdin>", &input)),
+            _ => read::read_file(path),
+        };
+
+        if let Err(e) = result {
+            error::report(&e);
+            status = exit_code(&e);
+        }
+    }
+
+    std::process::exit(status);
+}
+
+fn exit_on_error<T>(result: error::Result<T>) {
+    if let Err(e) = result {
+        report(&e);
+
+        std::process::exit(exit_code(&e));
+    }
+}
+
+/// Reports the error unless it comes from `OS.exit()`.
+fn report(e: &Error) {
+    if !matches!(e.kind, ErrorKind::Exit(_)) {
+        error::report(e);
+    }
 }
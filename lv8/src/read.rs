@@ -1,13 +1,58 @@
-use std::{fs, path::Path};
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::Path,
+};
 
-use lv8_common::error::Result;
+use lv8_common::{
+    error::{self, Error, Result},
+    source::SourceMap,
+};
 use lv8_parser::ASTNode;
 
 pub fn read_file<P>(path: &P) -> Result<ASTNode>
 where
     P: AsRef<Path>,
 {
-    let input = fs::read_to_string(path).unwrap();
+    let input = fs::read_to_string(path)
+        .map_err(|e| Error::io(&format!("cannot read {}: {}", path.as_ref().display(), e)))?;
 
-    lv8_parser::parse(&input)
+    // Imports are resolved to absolute paths, they are shown relative to the current
+    // directory in diagnostics when possible.
+    let current_dir = env::current_dir().unwrap_or_default();
+    let name = path
+        .as_ref()
+        .strip_prefix(&current_dir)
+        .unwrap_or(path.as_ref());
+
+    parse(&name.display().to_string(), &input)
+}
+
+/// The script piped to `lv8 -`.
+pub fn read_stdin() -> Result<String> {
+    let mut input = String::new();
+
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| Error::io(&format!("cannot read <stdin>: {}", e)))?;
+
+    Ok(input)
+}
+
+/// Parses a script, `name` is the file shown in diagnostics. Every syntax error of the
+/// script is shown, the last one stops the program.
+pub fn parse(name: &str, input: &str) -> Result<ASTNode> {
+    let file = SourceMap::add(name, input);
+    let (ast, mut errors) = lv8_parser::parse_recovering(input, file);
+
+    match errors.pop() {
+        Some(last) => {
+            for error in &errors {
+                error::report(error);
+            }
+
+            Err(last)
+        }
+        None => Ok(ast),
+    }
 }
@@ -0,0 +1,285 @@
+use std::{
+    borrow::Cow::{self, Owned},
+    fmt::Write,
+    fs,
+    path::Path,
+    rc::Rc,
+};
+
+use lv8_parser::{Keyword, Token, TokenKind};
+use owo_colors::OwoColorize;
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::{ValidationContext, ValidationResult, Validator},
+    Context, Helper, Result,
+};
+
+use crate::core::Evaluator;
+
+/// Completes, highlights and hints what is typed at the prompt from the live
+/// evaluator, and keeps reading lines while blocks are open.
+pub struct ReplHelper {
+    core: Rc<Evaluator>,
+}
+
+impl ReplHelper {
+    pub fn new(core: Rc<Evaluator>) -> Self {
+        Self { core }
+    }
+
+    /// Files and directories that can be imported, relative to the working directory.
+    fn complete_import(&self, partial: &str) -> Vec<Pair> {
+        let (directory, prefix) = match partial.rfind('/') {
+            Some(slash) => partial.split_at(slash + 1),
+            None => ("", partial),
+        };
+
+        let Ok(entries) = fs::read_dir(Path::new(&self.core.metadata.pw).join(directory)) else {
+            return Vec::new();
+        };
+
+        let mut candidates: Vec<Pair> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let mut name = entry.file_name().into_string().ok()?;
+
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+
+                if entry.file_type().ok()?.is_dir() {
+                    name.push('/');
+                } else if !name.ends_with(".lv") && !name.ends_with(".json") {
+                    return None;
+                }
+
+                Some(pair(name))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+
+        candidates
+    }
+
+    /// The names of the scope chain and the keywords, or the members of a module when
+    /// `path` leads to one.
+    fn complete_name(&self, path: &[&str], prefix: &str) -> Vec<Pair> {
+        let mut names = match path {
+            [] => {
+                let mut names = self.core.names();
+
+                names.extend(lv8_parser::keywords().map(str::to_string));
+                names
+            }
+            _ => self.core.members(path),
+        };
+
+        names.retain(|name| name.starts_with(prefix));
+        names.sort();
+        names.dedup();
+
+        names.into_iter().map(pair).collect()
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let tokens = lv8_parser::tokens(before);
+
+        // Nothing is completed after whitespace or in a comment.
+        let Some((last, previous)) = tokens.split_last() else {
+            return Ok((pos, Vec::new()));
+        };
+
+        if last.end != pos {
+            return Ok((pos, Vec::new()));
+        }
+
+        let text = &before[last.start..];
+
+        let completion = match last.kind {
+            TokenKind::String if is_unterminated(text) => match previous.last() {
+                Some(token) if token.kind == TokenKind::Keyword(Keyword::Import) => {
+                    let partial = &text[1..];
+                    let prefix = partial.rsplit('/').next().unwrap_or(partial);
+
+                    (pos - prefix.len(), self.complete_import(partial))
+                }
+                _ => (pos, Vec::new()),
+            },
+            TokenKind::Dot => (pos, self.complete_name(&path_at_end(previous, before), "")),
+            TokenKind::Identifier | TokenKind::Keyword(_) => {
+                let path = match previous.split_last() {
+                    Some((dot, rest)) if dot.kind == TokenKind::Dot => path_at_end(rest, before),
+                    _ => Vec::new(),
+                };
+
+                (last.start, self.complete_name(&path, text))
+            }
+            _ => (pos, Vec::new()),
+        };
+
+        Ok(completion)
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    /// The parameters left to pass when the cursor is at an empty argument of a call,
+    /// `area(` hints `width, height)`.
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+
+        let tokens = lv8_parser::tokens(line);
+        let last = tokens.last()?;
+
+        if !matches!(last.kind, TokenKind::LeftParen | TokenKind::Comma)
+            || !line[last.end..].trim().is_empty()
+        {
+            return None;
+        }
+
+        let mut depth = 0;
+        let mut commas = 0;
+
+        for (index, token) in tokens.iter().enumerate().rev() {
+            match token.kind {
+                TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace => {
+                    depth += 1
+                }
+                TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace
+                    if depth > 0 =>
+                {
+                    depth -= 1
+                }
+                TokenKind::LeftParen => {
+                    let path = path_at_end(&tokens[..index], line);
+                    let parameters = self.core.parameters(&path)?;
+                    let left = parameters.get(commas..).filter(|left| !left.is_empty())?;
+
+                    return Some(format!("{})", left.join(", ")));
+                }
+                // In an array or an object, not an argument.
+                TokenKind::LeftBracket | TokenKind::LeftBrace => return None,
+                TokenKind::Comma if depth == 0 => commas += 1,
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        let mut highlighted = String::with_capacity(line.len() * 2);
+        let mut end = 0;
+
+        for token in lv8_parser::tokens(line) {
+            highlight_comments(&mut highlighted, &line[end..token.start]);
+
+            let text = &line[token.start..token.end];
+
+            let _ = match token.kind {
+                TokenKind::Keyword(Keyword::True | Keyword::False) => {
+                    write!(highlighted, "{}", text.bright_blue())
+                }
+                TokenKind::Keyword(Keyword::Null | Keyword::Undefined) => {
+                    write!(highlighted, "{}", text.bright_black())
+                }
+                TokenKind::Keyword(_) => write!(highlighted, "{}", text.magenta()),
+                TokenKind::String => write!(highlighted, "{}", text.green()),
+                TokenKind::Integer | TokenKind::Float => write!(highlighted, "{}", text.yellow()),
+                _ => write!(highlighted, "{}", text),
+            };
+
+            end = token.end;
+        }
+
+        highlight_comments(&mut highlighted, &line[end..]);
+
+        Owned(highlighted)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Owned(hint.bright_black().to_string())
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _forced: bool) -> bool {
+        true
+    }
+}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> Result<ValidationResult> {
+        if lv8_parser::is_incomplete(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+impl Helper for ReplHelper {}
+
+fn pair(name: String) -> Pair {
+    Pair {
+        display: name.clone(),
+        replacement: name,
+    }
+}
+
+/// What is between two tokens is whitespace and comments, a comment runs from its `#`.
+fn highlight_comments(highlighted: &mut String, between: &str) {
+    match between.find('#') {
+        Some(comment) => {
+            let _ = write!(
+                highlighted,
+                "{}{}",
+                &between[..comment],
+                (&between[comment..]).bright_black()
+            );
+        }
+        None => highlighted.push_str(between),
+    }
+}
+
+/// The names of a path like `Math.pi` that `tokens` end with.
+fn path_at_end<'a>(tokens: &[Token], line: &'a str) -> Vec<&'a str> {
+    let mut path = Vec::new();
+    let mut rest = tokens;
+
+    while let [init @ .., word] = rest {
+        if !matches!(word.kind, TokenKind::Identifier | TokenKind::Keyword(_)) {
+            break;
+        }
+
+        path.push(&line[word.start..word.end]);
+
+        match init {
+            [init @ .., dot] if dot.kind == TokenKind::Dot => rest = init,
+            _ => break,
+        }
+    }
+
+    path.reverse();
+    path
+}
+
+/// Whether a string literal is still open, its last quote may be escaped.
+fn is_unterminated(literal: &str) -> bool {
+    match literal[1..].strip_suffix('"') {
+        Some(inner) => inner.chars().rev().take_while(|&char| char == '\\').count() % 2 == 1,
+        None => true,
+    }
+}
@@ -0,0 +1,77 @@
+use crate::core::{Evaluator, Metadata};
+use lv8_common::{
+    error::{self, Error, ErrorKind},
+    source::SourceMap,
+};
+use rustyline::{history::DefaultHistory, CompletionType, Config, Editor, Result};
+use std::rc::Rc;
+
+mod helper;
+
+use helper::ReplHelper;
+
+pub fn run(metadata: Metadata) -> Result<()> {
+    println!("Welcome to LV8 {}", env!("CARGO_PKG_VERSION"));
+    println!("Type 'exit' or Ctrl+C. to exit");
+    println!();
+
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+    let mut rl = Editor::<ReplHelper, DefaultHistory>::with_config(config)?;
+
+    let core = Rc::new(Evaluator::new(metadata));
+
+    rl.set_helper(Some(ReplHelper::new(Rc::clone(&core))));
+
+    let mut exit_code = None;
+
+    let mut i = 0;
+    loop {
+        let readline = rl.readline(&format!("LV8({})> ", i));
+
+        match readline {
+            Ok(line) => {
+                if line.trim() == "exit" {
+                    break;
+                }
+
+                i += 1;
+                rl.add_history_entry(line.as_str()).ok();
+                let file = SourceMap::add("<repl>", &line);
+                let ast = match lv8_parser::parse_interactive(&line, file) {
+                    Ok(ast) => ast,
+                    Err(err) => {
+                        error::report(&err);
+                        continue;
+                    }
+                };
+
+                match core.execute_interactive(ast) {
+                    Ok(Some(value)) => println!("{:?}", value),
+                    Ok(None) => {}
+                    Err(Error {
+                        kind: ErrorKind::Exit(code),
+                        ..
+                    }) => {
+                        exit_code = Some(code);
+                        break;
+                    }
+                    Err(e) => error::report(&e),
+                }
+            }
+
+            _ => break,
+        }
+    }
+
+    if let Err(e) = core.metadata.run_exit_handlers() {
+        error::report(&e);
+    }
+
+    if let Some(code) = exit_code {
+        std::process::exit(code);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Runs scripts using the `Array` module through the binary.
+
+mod common;
+
+use common::{error, stdout};
+
+const ITEMS: &str = r#"
+fun name(item) do
+  return item.name
+end
+
+items = [{n: 2, name: "a"}, {n: 1, name: "b"}, {n: 2, name: "c"}, {n: 1, name: "d"}, {n: 0, name: "e"}]
+"#;
+
+#[test]
+fn sort_with_boolean_comparator_is_stable() {
+    let output = stdout(&format!(
+        r#"{}
+fun less(a, b) do
+  result = false
+  if a.n < b.n do
+    result = true
+  end
+  return result
+end
+
+sorted = items.sort(less)
+names = sorted.map(name)
+printl(names.join(" "))"#,
+        ITEMS
+    ));
+
+    assert_eq!(output, "e b d a c\n");
+}
+
+#[test]
+fn sort_with_number_comparator() {
+    let output = stdout(
+        r#"fun descending(a, b) do
+  return b - a
+end
+
+sorted = Array.sort([3, 1, 2, 5, 4], descending)
+printl(sorted.join(" "))"#,
+    );
+
+    assert_eq!(output, "5 4 3 2 1\n");
+}
+
+#[test]
+fn sort_with_inconsistent_comparator() {
+    let output = stdout(&format!(
+        r#"{}
+fun always(a, b) do
+  return true
+end
+
+sorted = items.sort(always)
+printl(sorted.len())"#,
+        ITEMS
+    ));
+
+    assert_eq!(output, "5\n");
+}
+
+#[test]
+fn sort_stops_at_comparator_error() {
+    let output = error(
+        r#"fun boom(a, b) do
+  missing()
+end
+
+Array.sort([3, 1, 2, 5, 4], boom)"#,
+    );
+
+    assert_eq!(output, "ReferenceError: missing is not defined");
+}
+
+#[test]
+fn sort_rejects_other_comparator_results() {
+    let output = error(
+        r#"fun text(a, b) do
+  return "less"
+end
+
+Array.sort([2, 1], text)"#,
+    );
+
+    assert_eq!(
+        output,
+        "TypeError: Array.sort() comparator must return a number or boolean, got string"
+    );
+}
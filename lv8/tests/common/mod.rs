@@ -0,0 +1,77 @@
+//! Runs code given to `lv8 -e` and returns what it printed.
+
+// Every test file uses a different part of this module.
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+pub fn lv8(code: &str) -> Output {
+    lv8_in(&env::current_dir().unwrap(), code)
+}
+
+/// Runs `code` from `dir`, so relative paths and imports are resolved against it.
+pub fn lv8_in(dir: &Path, code: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_lv8"))
+        .args(["-e", code])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .expect("cannot run lv8")
+}
+
+/// The stdout of `code`, which must succeed.
+pub fn stdout(code: &str) -> String {
+    stdout_in(&env::current_dir().unwrap(), code)
+}
+
+pub fn stdout_in(dir: &Path, code: &str) -> String {
+    let output = lv8_in(dir, code);
+
+    assert!(
+        output.status.success(),
+        "the script failed:\n{}\n{}",
+        code,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The `SomeError: message` line of the stderr of `code`, which must fail with a runtime
+/// error.
+pub fn error(code: &str) -> String {
+    error_in(&env::current_dir().unwrap(), code)
+}
+
+pub fn error_in(dir: &Path, code: &str) -> String {
+    let output = lv8_in(dir, code);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(
+        output.status.code(),
+        Some(70),
+        "unexpected status for:\n{}\n{}",
+        code,
+        stderr
+    );
+
+    stderr
+        .lines()
+        .find(|line| !line.starts_with(' ') && line.contains("Error: "))
+        .unwrap_or_else(|| panic!("no error in:\n{}", stderr))
+        .to_string()
+}
+
+/// An empty directory for the files of a test, left behind for inspection.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("lv8-{}-{}", name, std::process::id()));
+
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
@@ -0,0 +1,111 @@
+//! Runs scripts using the `CSV` module through the binary.
+
+mod common;
+
+use common::{error, stdout, stdout_in, temp_dir};
+
+#[test]
+fn parse_quoted_fields() {
+    let output = stdout(
+        r#"rows = CSV.parse("name,city\nAda,London\n\"Smith, J\",\"New \"\"York\"\"\"\n")
+printl(JSON.encode(rows))"#,
+    );
+
+    assert_eq!(
+        output,
+        "[[\"name\",\"city\"],[\"Ada\",\"London\"],[\"Smith, J\",\"New \\\"York\\\"\"]]\n"
+    );
+}
+
+#[test]
+fn parse_options() {
+    let output = stdout(
+        r#"rows = CSV.parse("name;city\nAda;London\n", {delimiter: ";", header: true})
+printl(JSON.encode(rows))
+rows = CSV.parse("a,b\n1,2\n", {columns: ["x", "y"]})
+printl(JSON.encode(rows))
+rows = CSV.parse("a,b\n|x,y|,2\n", {quote: "|"})
+printl(JSON.encode(rows))
+rows = CSV.parse("a|b\n\"x\\\"y\"|2\n", {delimiter: "|", escape: "\\"})
+printl(JSON.encode(rows))
+rows = CSV.parse("a,b\n1\n", {header: true})
+printl(JSON.encode(rows))"#,
+    );
+
+    assert_eq!(
+        output,
+        concat!(
+            "[{\"city\":\"London\",\"name\":\"Ada\"}]\n",
+            "[{\"x\":\"a\",\"y\":\"b\"},{\"x\":\"1\",\"y\":\"2\"}]\n",
+            "[[\"a\",\"b\"],[\"x,y\",\"2\"]]\n",
+            "[[\"a\",\"b\"],[\"x\\\"y\",\"2\"]]\n",
+            "[{\"a\":\"1\",\"b\":null}]\n",
+        )
+    );
+}
+
+#[test]
+fn stringify_options() {
+    let output = stdout(
+        r#"print(CSV.stringify([["a", "b,c"], ["1", "say \"hi\""]]))
+print(CSV.stringify([["say \"hi\""]], {escape: "\\"}))
+print(CSV.stringify([{name: "Ada", city: "London", age: 36}], {columns: ["name", "city"], delimiter: ";"}))"#,
+    );
+
+    assert_eq!(
+        output,
+        "a,\"b,c\"\n1,\"say \"\"hi\"\"\"\n\"say \\\"hi\\\"\"\nname;city\nAda;London\n"
+    );
+}
+
+#[test]
+fn invalid_options() {
+    assert_eq!(
+        error(r#"CSV.parse("a", {delimiter: ";;"})"#),
+        "TypeError: CSV.parse() expects a single ASCII character for option \"delimiter\", got string"
+    );
+    assert_eq!(
+        error(r#"CSV.parse("a", {header: 1})"#),
+        "TypeError: CSV.parse() expects a boolean for option \"header\", got number"
+    );
+    assert_eq!(
+        error(r#"CSV.parse("a", {wat: 1})"#),
+        "ValueError: CSV.parse() unknown option \"wat\""
+    );
+}
+
+#[test]
+fn files() {
+    let dir = temp_dir("csv-files");
+
+    let output = stdout_in(
+        &dir,
+        r#"CSV.write("out.csv", [{a: 1, b: "x"}, {a: 2, b: "y"}], {columns: ["a", "b"]})
+print(FS.read_text("out.csv"))
+printl(JSON.encode(CSV.read("out.csv", {header: true})))
+
+fun show(row, index) do
+  printl(index, row.b)
+end
+
+CSV.each("out.csv", show, {header: true})
+
+reader = CSV.reader("out.csv", {header: true})
+printl(JSON.encode(reader.header))
+row = reader.next()
+printl(row.a)
+row = reader.next()
+row = reader.next()
+printl(row)"#,
+    );
+
+    assert_eq!(
+        output,
+        concat!(
+            "a,b\n1,x\n2,y\n",
+            "[{\"a\":\"1\",\"b\":\"x\"},{\"a\":\"2\",\"b\":\"y\"}]\n",
+            "0 x\n1 y\n",
+            "[\"a\",\"b\"]\n1\nnull\n",
+        )
+    );
+}
@@ -0,0 +1,18 @@
+//! Checks the exit status of scripts run through the binary.
+
+mod common;
+
+use common::lv8;
+
+fn status(code: &str) -> Option<i32> {
+    lv8(code).status.code()
+}
+
+#[test]
+fn statuses() {
+    assert_eq!(status(r#"printl("ok")"#), Some(0));
+    assert_eq!(status("OS.exit(1)"), Some(1));
+    assert_eq!(status("OS.exit(3)"), Some(3));
+    assert_eq!(status("missing()"), Some(70));
+    assert_eq!(status("x = (1"), Some(65));
+}
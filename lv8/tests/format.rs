@@ -0,0 +1,136 @@
+//! Runs scripts using `format` and `printf` through the binary. The expected outputs
+//! are what Python's `str.format` gives for the same specs.
+
+mod common;
+
+use common::{error, stdout};
+
+#[test]
+fn specs() {
+    let cases = [
+        ("{:>8.2f}", "3.14159", "    3.14"),
+        ("{:x}", "255", "ff"),
+        ("{:#X}", "255", "0XFF"),
+        ("{:#o}", "8", "0o10"),
+        ("{:#010b}", "5", "0b00000101"),
+        ("{:_x}", "3735928559", "dead_beef"),
+        ("{:,}", "1234567", "1,234,567"),
+        ("{:,.2f}", "1234567.891", "1,234,567.89"),
+        ("{:*^9}", r#""mid""#, "***mid***"),
+        ("{:<6}|", "42", "42    |"),
+        ("{:+d}", "7", "+7"),
+        ("{: d}", "7", " 7"),
+        ("{:=+8}", "42", "+     42"),
+        ("{:05}", "0 - 42", "-0042"),
+        ("{:08.3f}", "0 - 3.5", "-003.500"),
+        ("{:.3e}", "12345.678", "1.235e+04"),
+        ("{:E}", "0.00012", "1.200000E-04"),
+        ("{:g}", "0.0001234", "0.0001234"),
+        ("{:.3g}", "1234567.0", "1.23e+06"),
+        ("{:#.3g}", "1.0", "1.00"),
+        ("{:.1%}", "0.256", "25.6%"),
+        ("{}", "1.5", "1.5"),
+        ("{:.3}", r#""abcdef""#, "abc"),
+        ("{:10.3s}|", r#""abcdef""#, "abc       |"),
+    ];
+
+    let script = cases
+        .iter()
+        .map(|(spec, value, _)| format!(r#"printl(format("{}", {}))"#, spec, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let expected = cases
+        .iter()
+        .map(|(_, _, output)| format!("{}\n", output))
+        .collect::<String>();
+
+    assert_eq!(stdout(&script), expected);
+}
+
+#[test]
+fn fields() {
+    let output = stdout(
+        r#"printl(format("{1} {0} {{x}} {name}", "a", "b", {name: "n"}))
+template = "[{:>5}]"
+printl(template.format(3))
+printf("{} and {}\n", 1, 2)"#,
+    );
+
+    assert_eq!(output, "b a {x} n\n[    3]\n1 and 2\n");
+}
+
+#[test]
+fn invalid_specs() {
+    let cases = [
+        (
+            r#"format("{:q}", 1)"#,
+            r#"ValueError: format() invalid format spec "q""#,
+        ),
+        (
+            r#"format("{:.f}", 1)"#,
+            r#"ValueError: format() invalid format spec ".f""#,
+        ),
+        (
+            r#"format("{:d}", 1.5)"#,
+            "TypeError: format() cannot format number with 'd'",
+        ),
+        (
+            r#"format("{:f}", "s")"#,
+            "TypeError: format() cannot format string with 'f'",
+        ),
+        (
+            r#"format("{:=5}", "s")"#,
+            "ValueError: format() '=' alignment is only allowed for numbers",
+        ),
+        (
+            r#"format("{:,x}", 5)"#,
+            "ValueError: format() cannot use ',' with 'x'",
+        ),
+        (
+            r#"format("{:.1001f}", 1.5)"#,
+            r#"ValueError: format() format spec ".1001f" precision is larger than 1000"#,
+        ),
+        (
+            r#"format("{:.70000f}", 1.5)"#,
+            r#"ValueError: format() format spec ".70000f" precision is larger than 1000"#,
+        ),
+        (
+            r#"format("{:10001}", 1)"#,
+            r#"ValueError: format() format spec "10001" width is larger than 10000"#,
+        ),
+        (
+            r#"format("{:99999999999999999999999}", 1)"#,
+            r#"ValueError: format() format spec "99999999999999999999999" width is larger than 10000"#,
+        ),
+    ];
+
+    for (code, message) in cases {
+        assert_eq!(error(code), message, "for {}", code);
+    }
+}
+
+#[test]
+fn invalid_templates() {
+    let cases = [
+        (
+            r#"format("{", 1)"#,
+            "ValueError: format() unclosed '{' in template",
+        ),
+        (
+            r#"format("}", 1)"#,
+            "ValueError: format() single '}' in template",
+        ),
+        (
+            r#"format("{} {}", 1)"#,
+            "ValueError: format() missing argument at position 2",
+        ),
+        (
+            r#"format("{x}", {y: 1})"#,
+            r#"ValueError: format() missing named argument "x""#,
+        ),
+    ];
+
+    for (code, message) in cases {
+        assert_eq!(error(code), message, "for {}", code);
+    }
+}
@@ -0,0 +1,85 @@
+//! Runs scripts using the `JSON` module and JSON imports through the binary.
+
+mod common;
+
+use std::fs;
+
+use common::{error, error_in, stdout, stdout_in, temp_dir};
+
+#[test]
+fn encode() {
+    let output = stdout(
+        r#"printl(JSON.encode({name: "lv8", tags: ["fast", 1, 2.5, true, null], nested: {a: undefined}}))
+printl(JSON.encode("a\"b"))
+printl(JSON.encode(Time.from_unix(0, "+02:00")))"#,
+    );
+
+    assert_eq!(
+        output,
+        concat!(
+            "{\"name\":\"lv8\",\"nested\":{\"a\":null},\"tags\":[\"fast\",1,2.5,true,null]}\n",
+            "\"a\\\"b\"\n",
+            "\"1970-01-01T02:00:00+02:00\"\n",
+        )
+    );
+}
+
+#[test]
+fn encode_pretty() {
+    let output = stdout(r#"printl(JSON.encode([1, {b: 2}], true))"#);
+
+    assert_eq!(output, "[\n  1,\n  {\n    \"b\": 2\n  }\n]\n");
+}
+
+#[test]
+fn round_trip() {
+    let output = stdout(
+        r#"text = "{\"float\":1.0,\"int\":1,\"list\":[1,\"x\",null,false],\"object\":{\"key\":\"value\"}}"
+printl(JSON.encode(JSON.decode(text)))"#,
+    );
+
+    assert_eq!(
+        output,
+        "{\"float\":1.0,\"int\":1,\"list\":[1,\"x\",null,false],\"object\":{\"key\":\"value\"}}\n"
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        error(r#"JSON.decode("{")"#),
+        "ValueError: JSON.decode() invalid JSON: EOF while parsing an object at line 1 column 1"
+    );
+    assert_eq!(
+        error("JSON.encode(Math.sqrt(0 - 1))"),
+        "TypeError: JSON.encode() cannot encode NaN as JSON"
+    );
+}
+
+#[test]
+fn import() {
+    let dir = temp_dir("json-import");
+
+    fs::write(
+        dir.join("config.json"),
+        r#"{"name": "lv8", "version": 2, "tags": ["a"]}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("list.json"), "[1]").unwrap();
+
+    let output = stdout_in(
+        &dir,
+        r#"import "config.json" as Config
+printl(Config.name, Config.version, JSON.encode(Config.tags))"#,
+    );
+    let error = error_in(&dir, r#"import "list.json" as List"#);
+
+    assert_eq!(output, "lv8 2 [\"a\"]\n");
+    assert_eq!(
+        error,
+        format!(
+            "TypeError: cannot import {:?}: expected a JSON object, got array",
+            dir.join("list.json")
+        )
+    );
+}
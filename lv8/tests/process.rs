@@ -0,0 +1,53 @@
+//! Runs scripts using the `Process` module through the binary, with commands every Unix
+//! system has (`sh`, `cat` and `sleep`).
+
+#![cfg(unix)]
+
+mod common;
+
+use common::{error, stdout};
+
+#[test]
+fn run_returns_status_and_output() {
+    let output = stdout(
+        r#"result = Process.run("sh", ["-c", "echo out; echo err >&2; exit 3"])
+printl(result.status)
+print(result.stdout)
+print(result.stderr)"#,
+    );
+
+    assert_eq!(output, "3\nout\nerr\n");
+}
+
+#[test]
+fn run_writes_stdin() {
+    let output = stdout(
+        r#"result = Process.run("cat", [], {stdin: "first\nsecond\n"})
+print(result.stdout)"#,
+    );
+
+    assert_eq!(output, "first\nsecond\n");
+}
+
+#[test]
+fn run_times_out() {
+    assert_eq!(
+        error(r#"Process.run("sleep", ["5"], {timeout: 0.2})"#),
+        "IOError: Process.run() timed out after 0.2s"
+    );
+}
+
+#[test]
+fn spawn_writes_reads_and_waits() {
+    let output = stdout(
+        r#"child = Process.spawn("cat")
+child.write("hello\n")
+printl(child.read_line())
+child.close_stdin()
+printl(child.read_line())
+result = child.wait()
+printl(result.status)"#,
+    );
+
+    assert_eq!(output, "hello\nnull\n0\n");
+}
@@ -0,0 +1,58 @@
+//! Runs sandboxed scripts through the binary and checks they can't leave their roots.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+/// A fresh directory with `secret.txt` and `x/inside.txt`, the script runs from `x/y`.
+fn tree(name: &str) -> PathBuf {
+    let base = env::temp_dir().join(format!("lv8-sandbox-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+
+    fs::create_dir_all(base.join("x/y")).unwrap();
+    fs::write(base.join("secret.txt"), "secret").unwrap();
+    fs::write(base.join("x/inside.txt"), "inside").unwrap();
+
+    base
+}
+
+fn lv8(cwd: &Path, args: &[&str], script: &str) -> Output {
+    fs::write(cwd.join("script.lv"), script).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_lv8"))
+        .args(args)
+        .arg("script.lv")
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .output()
+        .expect("cannot run lv8")
+}
+
+#[test]
+fn roots_do_not_follow_chdir() {
+    let base = tree("chdir");
+    let cwd = base.join("x/y");
+
+    let output = lv8(
+        &cwd,
+        &["--sandbox", "--allow-read", ".."],
+        r#"printl(FS.read_text("../inside.txt"))
+OS.chdir("..")
+printl(FS.read_text("../inside.txt"))
+printl(FS.read_text("../../secret.txt"))"#,
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    fs::remove_dir_all(&base).unwrap();
+
+    assert_eq!(stdout, "inside\ninside\n");
+    assert_eq!(output.status.code(), Some(70));
+    assert!(
+        stderr.contains("PermissionError: FS.read_text() is not allowed"),
+        "unexpected error:\n{}",
+        stderr
+    );
+}
@@ -0,0 +1,94 @@
+//! Runs scripts using the `Time` module through the binary.
+
+mod common;
+
+use common::{error, stdout};
+
+fn offset(offset: &str) -> String {
+    stdout(&format!(
+        r#"printl(Time.format(Time.from_unix(0, "{}"), "%H:%M %:z"))"#,
+        offset
+    ))
+}
+
+#[test]
+fn offsets() {
+    assert_eq!(offset("Z"), "00:00 +00:00\n");
+    assert_eq!(offset("UTC"), "00:00 +00:00\n");
+    assert_eq!(offset("+02"), "02:00 +02:00\n");
+    assert_eq!(offset("-0530"), "18:30 -05:30\n");
+    assert_eq!(offset("+05:45"), "05:45 +05:45\n");
+}
+
+#[test]
+fn malformed_offsets() {
+    for offset in [
+        "", "+", "02", "+2", "+-1", "-+5", "+1:0", "+123", "+12:3", "+1200:", "+12-00", "+aé1",
+        "+12:60", "+24",
+    ] {
+        assert_eq!(
+            error(&format!(r#"Time.from_unix(0, "{}")"#, offset)),
+            format!("ValueError: Time.from_unix() invalid offset {:?}", offset)
+        );
+    }
+}
+
+#[test]
+fn parse_and_format() {
+    let output = stdout(
+        r#"start = Time.parse("2024-03-10T12:30:00+02:00")
+printl(Time.format(start, "%Y-%m-%d %H:%M:%S %:z"))
+printl(Time.unix(start))
+printl(Time.format(Time.to_utc(start), "%H:%M %:z"))
+printl(Time.format(Time.to_offset(start, "-05:30"), "%H:%M %:z"))
+printl(Time.format(Time.parse("2024-03-10"), "%Y-%m-%d %H:%M %:z"))
+printl(Time.format(Time.parse("10/03/2024 08:15", "%d/%m/%Y %H:%M"), "%Y-%m-%dT%H:%M%:z"))
+printl(JSON.encode(Time.parts(start)))"#,
+    );
+
+    assert_eq!(
+        output,
+        concat!(
+            "2024-03-10 12:30:00 +02:00\n",
+            "1710066600.0\n",
+            "10:30 +00:00\n",
+            "05:00 -05:30\n",
+            "2024-03-10 00:00 +00:00\n",
+            "2024-03-10T08:15+00:00\n",
+            "{\"day\":10,\"hour\":12,\"millisecond\":0,\"minute\":30,\"month\":3,",
+            "\"offset\":\"+02:00\",\"ordinal\":70,\"second\":0,\"weekday\":7,\"year\":2024}\n",
+        )
+    );
+}
+
+#[test]
+fn datetimes_and_durations() {
+    let output = stdout(
+        r#"start = Time.datetime({year: 2024, month: 2, day: 28, hour: 12, offset: "+01:00"})
+later = start.add({days: 1, hours: 2})
+printl(later.format("%Y-%m-%d %H:%M"))
+printl(Time.diff(later, start))
+
+if start < later do
+  printl("before")
+end"#,
+    );
+
+    assert_eq!(output, "2024-02-29 14:00\n93600.0\nbefore\n");
+}
+
+#[test]
+fn invalid_datetimes() {
+    assert_eq!(
+        error(r#"Time.parse("2024-13-01T00:00:00Z")"#),
+        "ValueError: Time.parse() invalid datetime \"2024-13-01T00:00:00Z\""
+    );
+    assert_eq!(
+        error("Time.datetime({year: 2024, month: 2, day: 30})"),
+        "ValueError: Time.datetime() invalid date or time"
+    );
+    assert_eq!(
+        error("Time.add(Time.utc(), {years: 1})"),
+        "ValueError: Time.add() unknown duration unit \"years\""
+    );
+}
@@ -1,47 +1,163 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display};
+
+use crate::source::Span;
+
+mod diagnostic;
+mod report;
+
+pub use diagnostic::{set_color_choice, suggest, ColorChoice};
+pub use report::{report, set_error_format, ErrorFormat, Report};
 
 pub struct Error {
     pub message: String,
     pub kind: ErrorKind,
+    /// Where in the source the error was raised, when known.
+    pub span: Option<Span>,
+    /// The call stack when the error was raised, outermost frame first.
+    pub traceback: Vec<Frame>,
+    /// Labels and notes shown with the source of the error.
+    pub annotations: Vec<Annotation>,
 }
 
-impl Error {
-    pub fn syntax(message: &str, syntax: SyntaxError) -> Self {
+pub enum Annotation {
+    /// Text under the span of the error.
+    Label(String),
+    /// Another span that explains the error, e.g. where an unclosed block started.
+    Secondary(Span, String),
+    Note(String),
+    Help(String),
+}
+
+/// A function, module or file being evaluated, `span` is the statement it was running.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub name: String,
+    pub span: Option<Span>,
+}
+
+impl Frame {
+    pub fn new(name: &str) -> Self {
         Self {
-            message: message.to_string(),
-            kind: ErrorKind::Syntax(syntax),
+            name: name.to_string(),
+            span: None,
         }
     }
+}
 
-    pub fn reference(message: &str) -> Self {
+impl Error {
+    fn new(message: String, kind: ErrorKind) -> Self {
         Self {
-            message: message.to_string(),
-            kind: ErrorKind::Reference,
+            message,
+            kind,
+            span: None,
+            traceback: Vec::new(),
+            annotations: Vec::new(),
         }
     }
 
+    pub fn syntax(message: &str, syntax: SyntaxError) -> Self {
+        Self::new(message.to_string(), ErrorKind::Syntax(Box::new(syntax)))
+    }
+
+    pub fn reference(message: &str) -> Self {
+        Self::new(message.to_string(), ErrorKind::Reference)
+    }
+
     pub fn r#type(message: &str) -> Self {
-        Self {
-            message: message.to_string(),
-            kind: ErrorKind::Type,
-        }
+        Self::new(message.to_string(), ErrorKind::Type)
+    }
+
+    pub fn value(message: &str) -> Self {
+        Self::new(message.to_string(), ErrorKind::Value)
+    }
+
+    pub fn io(message: &str) -> Self {
+        Self::new(message.to_string(), ErrorKind::Io)
+    }
+
+    /// Raised by `OS.exit`, unwinds the evaluation up to the CLI which exits with `code`.
+    pub fn exit(code: i32) -> Self {
+        Self::new(format!("exit with code {}", code), ErrorKind::Exit(code))
+    }
+
+    pub fn permission(message: &str) -> Self {
+        Self::new(message.to_string(), ErrorKind::Permission)
+    }
+
+    /// Records where the error happened, the innermost location is kept.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span.get_or_insert(span);
+
+        self
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.annotations.push(Annotation::Label(label.to_string()));
+
+        self
+    }
+
+    pub fn with_secondary_label(mut self, span: Span, label: &str) -> Self {
+        self.annotations
+            .push(Annotation::Secondary(span, label.to_string()));
+
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.annotations.push(Annotation::Note(note.to_string()));
+
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.annotations.push(Annotation::Help(help.to_string()));
+
+        self
     }
 }
 
+/// The full diagnostic: traceback, source snippet, labels and notes.
 impl Debug for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.kind {
-            ErrorKind::Syntax(error) => write!(f, "SyntaxError: {}\n{:?}", self.message, error),
-            ErrorKind::Reference => write!(f, "ReferenceError: {}", self.message),
-            ErrorKind::Type => write!(f, "TypeError: {}", self.message),
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", diagnostic::render(self, diagnostic::use_color()))
+    }
+}
+
+/// A single line like `TypeError: message`.
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::Exit(_) => write!(f, "{}", self.message),
+            _ => write!(f, "{}: {}", self.kind.name(), self.message),
         }
     }
 }
 
+impl std::error::Error for Error {}
+
 pub enum ErrorKind {
-    Syntax(SyntaxError),
+    Syntax(Box<SyntaxError>),
     Reference,
     Type,
+    Value,
+    Io,
+    Permission,
+    Exit(i32),
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Syntax(_) => "SyntaxError",
+            ErrorKind::Reference => "ReferenceError",
+            ErrorKind::Type => "TypeError",
+            ErrorKind::Value => "ValueError",
+            ErrorKind::Io => "IOError",
+            ErrorKind::Permission => "PermissionError",
+            ErrorKind::Exit(_) => "Exit",
+        }
+    }
 }
 
 pub struct SyntaxError {
@@ -51,7 +167,7 @@ pub struct SyntaxError {
 }
 
 impl Debug for SyntaxError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "  --> at line {}:{}\n  |\n{} | {}\n  |{}^\n",
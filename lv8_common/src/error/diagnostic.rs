@@ -0,0 +1,452 @@
+use std::{
+    collections::BTreeSet,
+    env,
+    fmt::Write,
+    io::{self, IsTerminal},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use super::{Annotation, Error, ErrorKind, Frame};
+use crate::source::{FileId, LineIndex, SourceMap, Span};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colors when stderr is a terminal and `NO_COLOR` is not set.
+    Auto,
+    Always,
+    Never,
+}
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+pub(super) fn use_color() -> bool {
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        choice if choice == ColorChoice::Always as u8 => true,
+        choice if choice == ColorChoice::Never as u8 => false,
+        _ => {
+            env::var_os("NO_COLOR").map_or(true, |value| value.is_empty())
+                && io::stderr().is_terminal()
+        }
+    }
+}
+
+/// The closest candidate to a misspelled `name`, if any is close enough to be a typo.
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let limit = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Edit distance between two strings in characters, swapping two neighbours counts as
+/// one edit since it is the most common typo.
+fn distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+
+    let mut rows = vec![vec![0; right.len() + 1]; left.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+
+            rows[i][j] = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    rows[left.len()][right.len()]
+}
+
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    const ERROR: &'static str = "1;31";
+    const SECONDARY: &'static str = "1;34";
+    const BOLD: &'static str = "1";
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Renders the traceback, the error and, when there is something to point at, the
+/// source with its labels, notes and help:
+///
+/// ```text
+/// SyntaxError: expected `end` to close `while` started on line 2
+///  --> main.lv:4:1
+///   |
+/// 2 | while x < 3 do
+///   | ----- `while` started here
+/// ...
+/// 4 |
+///   | ^ expected `end`
+///   |
+/// ```
+pub(super) fn render(error: &Error, color: bool) -> String {
+    let style = Style { color };
+    let mut out = String::new();
+
+    if let ErrorKind::Exit(_) = error.kind {
+        return error.message.clone();
+    }
+
+    if !error.traceback.is_empty() {
+        out.push_str("Traceback (most recent call last):\n");
+
+        for frame in &error.traceback {
+            write_frame(&mut out, frame);
+        }
+    }
+
+    let _ = write!(
+        out,
+        "{}: {}",
+        style.paint(Style::ERROR, error.kind.name()),
+        style.paint(Style::BOLD, &error.message)
+    );
+
+    let has_labels = error
+        .annotations
+        .iter()
+        .any(|annotation| matches!(annotation, Annotation::Label(_) | Annotation::Secondary(..)));
+
+    let mut width = 1;
+
+    // The traceback already shows the line of runtime errors, the source is only shown
+    // again when there is a label to put under it.
+    match error.span {
+        Some(span) if error.traceback.is_empty() || has_labels => {
+            width = write_snippets(&mut out, &style, span, &error.annotations);
+        }
+        None => {
+            if let ErrorKind::Syntax(syntax) = &error.kind {
+                let _ = write!(out, "\n{:?}", syntax);
+            }
+        }
+        _ => {}
+    }
+
+    for annotation in &error.annotations {
+        let (kind, text) = match annotation {
+            Annotation::Note(text) => ("note", text),
+            Annotation::Help(text) => ("help", text),
+            _ => continue,
+        };
+
+        let _ = write!(
+            out,
+            "\n{} {} {}: {}",
+            " ".repeat(width),
+            style.paint(Style::SECONDARY, "="),
+            style.paint(Style::BOLD, kind),
+            text
+        );
+    }
+
+    out
+}
+
+/// `  File "main.lv", line 3, in name` followed by the source line, like Python.
+fn write_frame(out: &mut String, frame: &Frame) {
+    let Some(span) = frame.span else {
+        let _ = writeln!(out, "  in {}", frame.name);
+        return;
+    };
+
+    let _ = writeln!(
+        out,
+        "  File {:?}, line {}, in {}",
+        span.file_name(),
+        span.line,
+        frame.name
+    );
+
+    let line = SourceMap::text(span.file).and_then(|text| {
+        text.lines()
+            .nth(span.line as usize - 1)
+            .map(|line| line.trim().to_string())
+    });
+
+    if let Some(line) = line.filter(|line| !line.is_empty()) {
+        let _ = writeln!(out, "    {}", line);
+    }
+}
+
+/// A label resolved to lines and columns, `end_column` is inclusive.
+struct Label<'a> {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    message: &'a str,
+    primary: bool,
+}
+
+impl Label<'_> {
+    fn is_multiline(&self) -> bool {
+        self.start_line != self.end_line
+    }
+}
+
+/// Writes one snippet per file the labels point into, the file of the error first.
+/// Returns the width of the line number gutter so notes can be aligned with it.
+fn write_snippets(
+    out: &mut String,
+    style: &Style,
+    span: Span,
+    annotations: &[Annotation],
+) -> usize {
+    let primary = annotations
+        .iter()
+        .find_map(|annotation| match annotation {
+            Annotation::Label(message) => Some(message.as_str()),
+            _ => None,
+        })
+        .unwrap_or("");
+
+    let mut labels = vec![(span, primary, true)];
+
+    labels.extend(
+        annotations
+            .iter()
+            .filter_map(|annotation| match annotation {
+                Annotation::Secondary(span, message) => Some((*span, message.as_str(), false)),
+                _ => None,
+            }),
+    );
+
+    let mut files: Vec<FileId> = Vec::new();
+
+    for (span, _, _) in &labels {
+        if !files.contains(&span.file) {
+            files.push(span.file);
+        }
+    }
+
+    let mut width = 1;
+
+    for file in files {
+        let labels: Vec<_> = labels
+            .iter()
+            .filter(|(span, _, _)| span.file == file)
+            .collect();
+
+        let location = labels[0].0;
+
+        match SourceMap::text(file) {
+            Some(text) => {
+                let labels: Vec<Label> = labels
+                    .iter()
+                    .map(|(span, message, primary)| resolve(&text, span, message, *primary))
+                    .collect();
+
+                width = write_snippet(out, style, location, &text, &labels);
+            }
+            None => {
+                let _ = write!(
+                    out,
+                    "\n {} {}",
+                    style.paint(Style::SECONDARY, "-->"),
+                    location
+                );
+            }
+        }
+    }
+
+    width
+}
+
+fn resolve<'a>(text: &str, span: &Span, message: &'a str, primary: bool) -> Label<'a> {
+    let lines = LineIndex::new(text);
+    let start = (span.start as usize).min(text.len());
+    let end = (span.end as usize).min(text.len());
+
+    // The end of a span is exclusive, the label goes up to its last character.
+    let last = if end > start {
+        text[..end]
+            .char_indices()
+            .next_back()
+            .map_or(start, |(index, _)| index)
+    } else {
+        start
+    };
+
+    let (start_line, start_column) = lines.line_col(text, start);
+    let (end_line, end_column) = lines.line_col(text, last);
+
+    Label {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+        message,
+        primary,
+    }
+}
+
+fn write_snippet(
+    out: &mut String,
+    style: &Style,
+    location: Span,
+    text: &str,
+    labels: &[Label],
+) -> usize {
+    let mut shown = BTreeSet::new();
+
+    for label in labels {
+        if label.end_line - label.start_line <= 4 {
+            shown.extend(label.start_line..=label.end_line);
+        } else {
+            shown.extend([
+                label.start_line,
+                label.start_line + 1,
+                label.end_line - 1,
+                label.end_line,
+            ]);
+        }
+    }
+
+    let width = shown.last().copied().unwrap_or(1).to_string().len();
+    let multiline = labels.iter().any(Label::is_multiline);
+    let source: Vec<&str> = text.lines().collect();
+
+    let gutter = |number: &str| {
+        style.paint(
+            Style::SECONDARY,
+            &format!("{:>width$} |", number, width = width),
+        )
+    };
+    let paint = |label: &Label, text: &str| {
+        let code = if label.primary {
+            Style::ERROR
+        } else {
+            Style::SECONDARY
+        };
+
+        style.paint(code, text)
+    };
+
+    let _ = write!(
+        out,
+        "\n{}{} {}\n{}",
+        " ".repeat(width),
+        style.paint(Style::SECONDARY, "-->"),
+        location,
+        gutter("")
+    );
+
+    let mut previous = None;
+
+    for line in shown {
+        if previous.is_some_and(|previous| line > previous + 1) {
+            let _ = write!(out, "\n{}", style.paint(Style::SECONDARY, "..."));
+        }
+
+        previous = Some(line);
+
+        let inside = labels.iter().find(|label| {
+            label.is_multiline() && label.start_line < line && line <= label.end_line
+        });
+        let prefix = match (multiline, inside) {
+            (false, _) => String::new(),
+            (true, Some(label)) => format!("{} ", paint(label, "|")),
+            (true, None) => "  ".to_string(),
+        };
+
+        let content = source
+            .get(line - 1)
+            .map(|content| content.trim_end_matches('\r').replace('\t', " "))
+            .unwrap_or_default();
+
+        let _ = write!(out, "\n{} {}{}", gutter(&line.to_string()), prefix, content);
+
+        let mut single: Vec<&Label> = labels
+            .iter()
+            .filter(|label| !label.is_multiline() && label.start_line == line)
+            .collect();
+
+        single.sort_by_key(|label| label.start_column);
+
+        for label in single {
+            let marker = if label.primary { "^" } else { "-" };
+            let marks = marker.repeat(label.end_column - label.start_column + 1);
+
+            let _ = write!(
+                out,
+                "\n{} {}{}{}",
+                gutter(""),
+                prefix,
+                " ".repeat(label.start_column - 1),
+                paint(label, format!("{} {}", marks, label.message).trim_end())
+            );
+        }
+
+        for label in labels.iter().filter(|label| label.is_multiline()) {
+            let marker = if label.primary { "^" } else { "-" };
+
+            if label.start_line == line {
+                let _ = write!(
+                    out,
+                    "\n{} {}",
+                    gutter(""),
+                    paint(
+                        label,
+                        &format!(" {}{}", "_".repeat(label.start_column), marker)
+                    )
+                );
+            }
+
+            if label.end_line == line {
+                let _ = write!(
+                    out,
+                    "\n{} {}",
+                    gutter(""),
+                    paint(
+                        label,
+                        format!(
+                            "|{}{} {}",
+                            "_".repeat(label.end_column),
+                            marker,
+                            label.message
+                        )
+                        .trim_end()
+                    )
+                );
+            }
+        }
+    }
+
+    let _ = write!(out, "\n{}", gutter(""));
+
+    width
+}
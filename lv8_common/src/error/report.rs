@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::Serialize;
+
+use super::{diagnostic, Annotation, Error, ErrorKind};
+use crate::source::Span;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// The rendered diagnostic, see [`Error`]'s `Debug` implementation.
+    Human,
+    /// One JSON object per line, for editors and CI.
+    Json,
+}
+
+static ERROR_FORMAT: AtomicU8 = AtomicU8::new(ErrorFormat::Human as u8);
+
+pub fn set_error_format(format: ErrorFormat) {
+    ERROR_FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+/// Prints an error to stderr in the format chosen with [`set_error_format`].
+pub fn report(error: &Error) {
+    if ERROR_FORMAT.load(Ordering::Relaxed) == ErrorFormat::Json as u8
+        && !matches!(error.kind, ErrorKind::Exit(_))
+    {
+        eprintln!("{}", serde_json::to_string(&error.report()).unwrap());
+    } else {
+        eprintln!("{}", diagnostic::render(error, diagnostic::use_color()));
+    }
+}
+
+/// The serializable form of an [`Error`], positions are 1-based and spans are byte
+/// offsets.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub kind: &'static str,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub span: Option<Range>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+    /// Outermost frame first.
+    pub traceback: Vec<Frame>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Range {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Label {
+    pub message: String,
+    pub primary: bool,
+    #[serde(flatten)]
+    pub location: Location,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Frame {
+    pub name: String,
+    #[serde(flatten)]
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Location {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub span: Range,
+}
+
+impl From<Span> for Location {
+    fn from(span: Span) -> Self {
+        Self {
+            file: span.file_name(),
+            line: span.line,
+            column: span.column,
+            span: Range {
+                start: span.start,
+                end: span.end,
+            },
+        }
+    }
+}
+
+impl Error {
+    pub fn report(&self) -> Report {
+        let mut labels = Vec::new();
+        let mut notes = Vec::new();
+        let mut help = Vec::new();
+
+        for annotation in &self.annotations {
+            match annotation {
+                Annotation::Label(message) => {
+                    if let Some(span) = self.span {
+                        labels.push(Label {
+                            message: message.clone(),
+                            primary: true,
+                            location: span.into(),
+                        });
+                    }
+                }
+                Annotation::Secondary(span, message) => labels.push(Label {
+                    message: message.clone(),
+                    primary: false,
+                    location: (*span).into(),
+                }),
+                Annotation::Note(note) => notes.push(note.clone()),
+                Annotation::Help(text) => help.push(text.clone()),
+            }
+        }
+
+        Report {
+            kind: self.kind.name(),
+            message: self.message.clone(),
+            file: self.span.map(|span| span.file_name()),
+            line: self.span.map(|span| span.line),
+            column: self.span.map(|span| span.column),
+            span: self.span.map(|span| Range {
+                start: span.start,
+                end: span.end,
+            }),
+            labels,
+            notes,
+            help,
+            traceback: self
+                .traceback
+                .iter()
+                .map(|frame| Frame {
+                    name: frame.name.clone(),
+                    location: frame.span.map(Location::from),
+                })
+                .collect(),
+        }
+    }
+}
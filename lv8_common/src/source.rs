@@ -0,0 +1,110 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+/// Identifies a parsed source in the [`SourceMap`], spans only store this id so the AST
+/// stays small. The default id belongs to no source.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FileId(pub u32);
+
+/// A region of a source. `start` and `end` are byte offsets, `line` and `column` point at
+/// `start` and are 1-based, the column counts characters. Positions are stored as `u32`
+/// to keep the AST and errors small.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub file: FileId,
+    pub start: u32,
+    pub end: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Span {
+    /// The span from the start of `self` to the end of `other`.
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            end: other.end,
+            ..*self
+        }
+    }
+
+    /// The name of the file the span points into.
+    pub fn file_name(&self) -> String {
+        SourceMap::name(self.file).unwrap_or_else(|| "<unknown>".to_string())
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file_name(), self.line, self.column)
+    }
+}
+
+/// Byte offsets of the start of every line, used to turn offsets into lines and columns
+/// without rescanning the source.
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self { starts }
+    }
+
+    /// The 1-based line and column of a byte offset of `text`.
+    pub fn line_col(&self, text: &str, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let column = text[self.starts[line]..offset].chars().count();
+
+        (line + 1, column + 1)
+    }
+}
+
+struct Source {
+    name: String,
+    text: Rc<str>,
+}
+
+thread_local! {
+    static SOURCES: RefCell<Vec<Source>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Every source parsed by the program (files, imports, REPL lines), so diagnostics can
+/// show the file name and the text a span points at.
+pub struct SourceMap;
+
+impl SourceMap {
+    pub fn add(name: &str, text: &str) -> FileId {
+        SOURCES.with(|sources| {
+            let mut sources = sources.borrow_mut();
+
+            sources.push(Source {
+                name: name.to_string(),
+                text: Rc::from(text),
+            });
+
+            FileId(sources.len() as u32)
+        })
+    }
+
+    pub fn name(file: FileId) -> Option<String> {
+        SOURCES.with(|sources| {
+            sources
+                .borrow()
+                .get((file.0 as usize).wrapping_sub(1))
+                .map(|source| source.name.clone())
+        })
+    }
+
+    pub fn text(file: FileId) -> Option<Rc<str>> {
+        SOURCES.with(|sources| {
+            sources
+                .borrow()
+                .get((file.0 as usize).wrapping_sub(1))
+                .map(|source| Rc::clone(&source.text))
+        })
+    }
+}
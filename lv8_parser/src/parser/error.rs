@@ -0,0 +1,59 @@
+use lv8_common::error::{Error, SyntaxError};
+
+use super::{
+    lexer::{Token, TokenKind},
+    Context,
+};
+
+/// A syntax error pointing at `start..end` of the source.
+pub fn syntax(context: &Context, start: usize, end: usize, message: &str) -> Error {
+    let span = context.span(start, end);
+    let line_start = context.input[..start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line = context.input[line_start..].lines().next().unwrap_or("");
+
+    Error::syntax(
+        message,
+        SyntaxError::new(line, span.line as usize, span.column as usize),
+    )
+    .with_span(span)
+}
+
+/// "expected `do`, found `print`", pointing at the token that was found.
+pub fn expected(context: &Context, found: Token, expected: &str) -> Error {
+    syntax(
+        context,
+        found.start,
+        found.end,
+        &format!("expected {}, found {}", expected, describe(context, found)),
+    )
+    .with_label(&format!("expected {}", expected))
+}
+
+/// The error for a block still open at the end of the file, pointing at both ends.
+pub fn unclosed(context: &Context, found: Token, opened: Token) -> Error {
+    let keyword = context.text(opened);
+    let opened = context.span(opened.start, opened.end);
+
+    syntax(
+        context,
+        found.start,
+        found.end,
+        &format!(
+            "expected `end` to close `{}` started on line {}",
+            keyword, opened.line
+        ),
+    )
+    .with_label("expected `end`")
+    .with_secondary_label(opened, &format!("`{}` started here", keyword))
+}
+
+fn describe(context: &Context, token: Token) -> String {
+    match token.kind {
+        TokenKind::Eof => "end of file".to_string(),
+        TokenKind::String => "a string".to_string(),
+        TokenKind::Integer | TokenKind::Float => format!("number `{}`", context.text(token)),
+        _ => format!("`{}`", context.text(token)),
+    }
+}
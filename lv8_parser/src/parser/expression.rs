@@ -1,194 +1,381 @@
-use pest::{iterators::Pair, pratt_parser::PrattParser};
+use std::collections::HashMap;
 
 use super::{
-    ComparisonExpression, ComparisonOperation, Expression, LogicExpression, MathExpression,
-    MathOperation, Rule,
+    error, statement::is_word, ComparisonExpression, ComparisonOperation, Expression,
+    ExpressionKind, Keyword, LogicExpression, MathExpression, MathOperation, Parser, TokenKind,
 };
 use crate::Either;
 use lv8_common::error::Result;
 
-lazy_static::lazy_static! {
-    static ref MATH_PRATT_PARSER: PrattParser<Rule> = {
-        use pest::pratt_parser::{Assoc::*, Op};
-        use super::super::Rule;
+/// The binding power of `not`, weaker than comparisons so `not a == b` is `not (a == b)`.
+const NOT_PRECEDENCE: u8 = 20;
 
-        // Precedence is defined lowest to highest
-        PrattParser::new()
-            .op(Op::infix(Rule::add, Left) | Op::infix(Rule::subtract, Left))
-            .op(Op::infix(Rule::multiply, Left) | Op::infix(Rule::divide, Left) | Op::infix(Rule::modulus, Left))
-            .op(Op::infix(Rule::exponentiation, Right))
-    };
+enum Operator {
+    Math(MathOperation),
+    Comparison(ComparisonOperation),
+    And,
+    Or,
 }
 
-lazy_static::lazy_static! {
-    static ref LOGIC_PRATT_PARSER: PrattParser<Rule> = {
-        use pest::pratt_parser::{Assoc::*, Op};
-        use super::super::Rule;
-
-        // Precedence is defined lowest to highest
-        PrattParser::new()
-        .op(Op::infix(Rule::and, Left) | Op::infix(Rule::or, Left))
-        .op(Op::prefix(Rule::not))
-        .op(
-            Op::infix(Rule::greather_eq, Left)
-            | Op::infix(Rule::less_eq, Left)
-            | Op::infix(Rule::greather, Left)
-            | Op::infix(Rule::less, Left)
-            | Op::infix(Rule::equal, Left)
-            | Op::infix(Rule::not_equal, Left)
-        )
+/// Infix operators with their precedence, higher binds tighter, and whether they are
+/// right associative. Comparisons and `and`/`or` only exist in conditions.
+fn operator(kind: TokenKind, condition: bool) -> Option<(Operator, u8, bool)> {
+    let operator = match kind {
+        TokenKind::Plus => (Operator::Math(MathOperation::Add), 40, false),
+        TokenKind::Minus => (Operator::Math(MathOperation::Subtract), 40, false),
+        TokenKind::Star => (Operator::Math(MathOperation::Multiply), 50, false),
+        TokenKind::Slash => (Operator::Math(MathOperation::Divide), 50, false),
+        TokenKind::Percent => (Operator::Math(MathOperation::Modulus), 50, false),
+        TokenKind::StarStar => (Operator::Math(MathOperation::Exponentiation), 60, true),
+        _ if !condition => return None,
+        TokenKind::Keyword(Keyword::And) | TokenKind::AndAnd => (Operator::And, 10, false),
+        TokenKind::Keyword(Keyword::Or) | TokenKind::OrOr => (Operator::Or, 10, false),
+        kind => {
+            let operation = match kind {
+                TokenKind::Keyword(Keyword::Is) | TokenKind::Equal => ComparisonOperation::Equal,
+                TokenKind::NotEqual => ComparisonOperation::NotEqual,
+                TokenKind::Greater => ComparisonOperation::GreaterThan,
+                TokenKind::GreaterEqual => ComparisonOperation::GreaterThanOrEqual,
+                TokenKind::Less => ComparisonOperation::LessThan,
+                TokenKind::LessEqual => ComparisonOperation::LessThanOrEqual,
+                _ => return None,
+            };
+
+            (Operator::Comparison(operation), 30, false)
+        }
     };
+
+    Some(operator)
 }
 
-pub fn parse(pair: Pair<Rule>) -> Result<Expression> {
-    match pair.as_rule() {
-        Rule::null => Ok(Expression::Null),
+/// Whether a value can take part in math: numbers, names and math itself.
+fn is_operand(expression: &Expression) -> bool {
+    matches!(
+        expression.kind,
+        ExpressionKind::Number(_)
+            | ExpressionKind::Identifier(_)
+            | ExpressionKind::Namespace(_)
+            | ExpressionKind::MathExpression(_)
+    )
+}
 
-        Rule::undefined => Ok(Expression::Undefined),
+pub(super) fn starts_expression(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Integer
+            | TokenKind::Float
+            | TokenKind::Minus
+            | TokenKind::String
+            | TokenKind::Identifier
+            | TokenKind::LeftParen
+            | TokenKind::LeftBrace
+            | TokenKind::LeftBracket
+            | TokenKind::Keyword(
+                Keyword::Null | Keyword::Undefined | Keyword::True | Keyword::False
+            )
+    )
+}
 
-        Rule::boolean => Ok(Expression::Boolean(pair.as_str().parse().unwrap())),
+impl Parser<'_> {
+    /// A value: a literal, a name or math.
+    pub(super) fn expression(&mut self) -> Result<Expression> {
+        self.binary(0, false)
+    }
 
-        Rule::number => {
-            let inner = pair.into_inner().next().unwrap();
+    /// The condition of `if`, `elseif` and `while`, which can also compare values and
+    /// combine them with `and`, `or` and `not`.
+    pub(super) fn condition(&mut self) -> Result<Expression> {
+        self.binary(0, true)
+    }
 
-            match inner.as_rule() {
-                Rule::integer => Ok(Expression::Number(Either::Left(
-                    inner.as_str().parse().unwrap(),
-                ))),
-                Rule::float => Ok(Expression::Number(Either::Right(
-                    inner.as_str().parse().unwrap(),
-                ))),
-                _ => unreachable!(),
+    fn binary(&mut self, min_precedence: u8, condition: bool) -> Result<Expression> {
+        let mut left = self.prefix(condition)?;
+
+        while let Some((operator, precedence, right_associative)) =
+            operator(self.peek().kind, condition)
+        {
+            if precedence <= min_precedence {
+                break;
+            }
+
+            let is_math = matches!(operator, Operator::Math(_));
+
+            // `"a" + 1` is not math, the `+` is left for the caller to report.
+            if is_math && !is_operand(&left) {
+                break;
             }
+
+            self.advance();
+
+            let precedence = if right_associative {
+                precedence - 1
+            } else {
+                precedence
+            };
+
+            let right = if is_math {
+                self.operand(precedence)?
+            } else {
+                self.binary(precedence, condition)?
+            };
+
+            let span = left.span.to(&right.span);
+            let (left_box, right) = (Box::new(left), Box::new(right));
+
+            let kind = match operator {
+                Operator::Math(operation) => {
+                    ExpressionKind::MathExpression(MathExpression::Operation {
+                        left: left_box,
+                        operation,
+                        right,
+                    })
+                }
+                Operator::Comparison(operation) => {
+                    ExpressionKind::ComparisonExpression(ComparisonExpression {
+                        left: left_box,
+                        operation,
+                        right,
+                    })
+                }
+                Operator::And => ExpressionKind::LogicExpression(LogicExpression::And {
+                    left: left_box,
+                    right,
+                }),
+                Operator::Or => ExpressionKind::LogicExpression(LogicExpression::Or {
+                    left: left_box,
+                    right,
+                }),
+            };
+
+            left = Expression::new(kind, span);
         }
-        Rule::ident => Ok(Expression::Identifier(pair.as_str().to_string())),
 
-        Rule::string => {
-            let inner = pair.into_inner().next().unwrap();
-            Ok(Expression::String(inner.as_str().to_string()))
+        Ok(left)
+    }
+
+    /// The right side of a math operator: a number, a name or math in parentheses.
+    fn operand(&mut self, min_precedence: u8) -> Result<Expression> {
+        match self.peek().kind {
+            TokenKind::Integer
+            | TokenKind::Float
+            | TokenKind::Minus
+            | TokenKind::Identifier
+            | TokenKind::LeftParen => self.binary(min_precedence, false),
+            _ => Err(self.expected("a number, a name or `(`")),
         }
+    }
 
-        Rule::object => {
-            let mut object = std::collections::HashMap::new();
+    fn prefix(&mut self, condition: bool) -> Result<Expression> {
+        let token = self.peek();
 
-            for pair in pair.into_inner() {
-                let mut inner = pair.into_inner();
+        let kind = match token.kind {
+            TokenKind::Keyword(Keyword::Not) | TokenKind::Bang if condition => {
+                self.advance();
 
-                let key = match parse(inner.next().unwrap())? {
-                    Expression::String(key) => key,
-                    Expression::Identifier(key) => key,
-                    _ => unreachable!(),
-                };
+                let expr = self.binary(NOT_PRECEDENCE - 1, true)?;
+                let span = self.context.span(token.start, token.end).to(&expr.span);
 
-                let value = parse(inner.next().unwrap())?;
-                object.insert(key, value);
+                return Ok(Expression::new(
+                    ExpressionKind::LogicExpression(LogicExpression::Not {
+                        expr: Box::new(expr),
+                    }),
+                    span,
+                ));
             }
+            TokenKind::LeftParen => {
+                self.advance();
 
-            Ok(Expression::Object(object))
-        }
+                let inner = self.binary(0, condition)?;
 
-        Rule::array => {
-            let mut array = Vec::new();
+                self.expect(TokenKind::RightParen, "`)`")?;
 
-            for pair in pair.into_inner() {
-                array.push(parse(pair)?);
+                return Ok(inner);
             }
+            TokenKind::Minus
+                if matches!(self.nth(1).kind, TokenKind::Integer | TokenKind::Float)
+                    && self.nth(1).start == token.end =>
+            {
+                self.advance();
 
-            Ok(Expression::Array(array))
-        }
+                return self.number(token.start);
+            }
+            TokenKind::Integer | TokenKind::Float => return self.number(token.start),
+            TokenKind::Identifier => return self.name(),
+            TokenKind::String => {
+                self.advance();
 
-        Rule::expr => {
-            let inner = pair.into_inner().next().unwrap();
-            Ok(parse(inner)?)
-        }
+                ExpressionKind::String(string_value(self.text(token)))
+            }
+            TokenKind::Keyword(Keyword::Null) => {
+                self.advance();
+                ExpressionKind::Null
+            }
+            TokenKind::Keyword(Keyword::Undefined) => {
+                self.advance();
+                ExpressionKind::Undefined
+            }
+            TokenKind::Keyword(Keyword::True) => {
+                self.advance();
+                ExpressionKind::Boolean(true)
+            }
+            TokenKind::Keyword(Keyword::False) => {
+                self.advance();
+                ExpressionKind::Boolean(false)
+            }
+            TokenKind::LeftBrace => self.object()?,
+            TokenKind::LeftBracket => self.array()?,
+            _ => return Err(self.expected("an expression")),
+        };
 
-        Rule::math_expr => parse_math_expression(pair),
+        Ok(Expression::new(kind, self.span_from(token)))
+    }
 
-        Rule::logic_expr => parse_logic_expression(pair),
+    /// A number literal, `start` is where its `-` sign is, if any.
+    fn number(&mut self, start: usize) -> Result<Expression> {
+        let token = self.advance();
+        let text = &self.context.input[start..token.end];
+
+        let number = match token.kind {
+            TokenKind::Integer => match text.parse() {
+                Ok(integer) => Either::Left(integer),
+                Err(_) => {
+                    return Err(error::syntax(
+                        self.context,
+                        start,
+                        token.end,
+                        "integer literal is too large",
+                    )
+                    .with_label(&format!(
+                        "integers go from {} to {}",
+                        isize::MIN,
+                        isize::MAX
+                    )))
+                }
+            },
+            _ => Either::Right(text.parse().unwrap()),
+        };
+
+        Ok(Expression::new(
+            ExpressionKind::Number(number),
+            self.context.span(start, token.end),
+        ))
+    }
 
-        Rule::namespace => {
-            let mut namespace = Vec::new();
+    /// A name or a path into a module like `Math.pow`.
+    pub(super) fn name(&mut self) -> Result<Expression> {
+        let first = self.expect(TokenKind::Identifier, "a name")?;
+        let mut path = vec![self.text(first).to_string()];
 
-            for pair in pair.into_inner() {
-                namespace.push(pair.as_str().to_string());
+        while self.eat(TokenKind::Dot).is_some() {
+            if !is_word(self.peek().kind) {
+                return Err(self.expected("a name after `.`"));
             }
 
-            Ok(Expression::Namespace(namespace))
+            let member = self.advance();
+
+            path.push(self.text(member).to_string());
         }
 
-        _ => unreachable!("unreachable!() in expression.rs, {:?}", pair.as_rule()),
+        let kind = match path.len() {
+            1 => ExpressionKind::Identifier(path.pop().unwrap()),
+            _ => ExpressionKind::Namespace(path),
+        };
+
+        Ok(Expression::new(kind, self.span_from(first)))
     }
-}
 
-fn parse_math_expression(pair: Pair<Rule>) -> Result<Expression> {
-    let pairs = pair.into_inner();
-
-    MATH_PRATT_PARSER
-        .map_primary(parse)
-        .map_infix(|left, op, right| {
-            let op = match op.as_rule() {
-                Rule::add => MathOperation::Add,
-                Rule::subtract => MathOperation::Subtract,
-                Rule::multiply => MathOperation::Multiply,
-                Rule::divide => MathOperation::Divide,
-                Rule::exponentiation => MathOperation::Exponentiation,
-                Rule::modulus => MathOperation::Modulus,
-                _ => unreachable!("unreachable!() in expression.rs, {:?}", op.as_rule()),
-            };
+    fn object(&mut self) -> Result<ExpressionKind> {
+        self.advance();
+
+        let mut object = HashMap::new();
+
+        if self.eat(TokenKind::RightBrace).is_some() {
+            return Ok(ExpressionKind::Object(object));
+        }
 
-            let math_expr = MathExpression::Operation {
-                left: Box::new(left?),
-                operation: op,
-                right: Box::new(right?),
+        loop {
+            let key = self.peek();
+
+            let key = match key.kind {
+                TokenKind::String => string_value(self.text(key)),
+                kind if is_word(kind) => self.text(key).to_string(),
+                _ => return Err(self.expected("a key")),
             };
 
-            Ok(Expression::MathExpression(math_expr))
-        })
-        .parse(pairs)
+            self.advance();
+            self.expect(TokenKind::Colon, "`:`")?;
+
+            object.insert(key, self.expression()?);
+
+            if self.eat(TokenKind::Comma).is_none() {
+                self.expect(TokenKind::RightBrace, "`,` or `}`")?;
+
+                return Ok(ExpressionKind::Object(object));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<ExpressionKind> {
+        self.advance();
+
+        let mut array = Vec::new();
+
+        if self.eat(TokenKind::RightBracket).is_some() {
+            return Ok(ExpressionKind::Array(array));
+        }
+
+        loop {
+            array.push(self.expression()?);
+
+            if self.eat(TokenKind::Comma).is_none() {
+                self.expect(TokenKind::RightBracket, "`,` or `]`")?;
+
+                return Ok(ExpressionKind::Array(array));
+            }
+        }
+    }
 }
 
-fn parse_logic_expression(pair: Pair<Rule>) -> Result<Expression> {
-    let pairs = pair.into_inner();
+/// The value of a string literal with its quotes, the closing one is missing when the
+/// string is unterminated.
+pub(super) fn string_value(literal: &str) -> String {
+    let inner = &literal[1..];
+
+    unescape(inner.strip_suffix('"').unwrap_or(inner))
+}
 
-    LOGIC_PRATT_PARSER
-        .map_primary(parse)
-        .map_prefix(|_, right| {
-            let right = right?;
+/// Resolves the escape sequences of a string literal, the lexer only lets valid ones
+/// through. Lone surrogates in `\u` escapes become U+FFFD.
+pub fn unescape(literal: &str) -> String {
+    let mut result = String::with_capacity(literal.len());
+    let mut chars = literal.chars();
 
-            let logic_expr = LogicExpression::Not {
-                expr: Box::new(right),
-            };
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            result.push(char);
+            continue;
+        }
 
-            Ok(Expression::LogicExpression(logic_expr))
-        })
-        .map_infix(|left, op, right| match op.as_rule() {
-            Rule::and => Ok(Expression::LogicExpression(LogicExpression::And {
-                left: Box::new(left?),
-                right: Box::new(right?),
-            })),
-            Rule::or => Ok(Expression::LogicExpression(LogicExpression::Or {
-                left: Box::new(left?),
-                right: Box::new(right?),
-            })),
-            _ => {
-                let op = match op.as_rule() {
-                    Rule::equal => ComparisonOperation::Equal,
-                    Rule::not_equal => ComparisonOperation::NotEqual,
-                    Rule::greather => ComparisonOperation::GreaterThan,
-                    Rule::less => ComparisonOperation::LessThan,
-                    Rule::greather_eq => ComparisonOperation::GreaterThanOrEqual,
-                    Rule::less_eq => ComparisonOperation::LessThanOrEqual,
-                    _ => unreachable!("unreachable!() in expression.rs, {:?}", op.as_rule()),
-                };
-
-                let comp_expr = ComparisonExpression {
-                    left: Box::new(left?),
-                    operation: op,
-                    right: Box::new(right?),
-                };
-
-                Ok(Expression::ComparisonExpression(comp_expr))
+        match chars.next() {
+            Some('b') => result.push('\u{8}'),
+            Some('f') => result.push('\u{c}'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('u') => {
+                let code: String = chars.by_ref().take(4).collect();
+
+                result.push(
+                    u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER),
+                );
             }
-        })
-        .parse(pairs)
+            Some(escaped) => result.push(escaped),
+            None => result.push('\\'),
+        }
+    }
+
+    result
 }
@@ -0,0 +1,462 @@
+use lv8_common::error::Error;
+
+use super::{error, Context};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keyword {
+    Fun,
+    Do,
+    End,
+    If,
+    Elseif,
+    Else,
+    While,
+    Module,
+    Import,
+    As,
+    Return,
+    Break,
+    Continue,
+    And,
+    Or,
+    Not,
+    Is,
+    True,
+    False,
+    Null,
+    Undefined,
+}
+
+/// How every keyword is written, for completion. Lexing goes through the `match` in
+/// `Keyword::from_word`, which must list the same words.
+pub const KEYWORDS: [&str; 21] = [
+    "fun",
+    "do",
+    "end",
+    "if",
+    "elseif",
+    "else",
+    "while",
+    "module",
+    "import",
+    "as",
+    "return",
+    "break",
+    "continue",
+    "and",
+    "or",
+    "not",
+    "is",
+    "true",
+    "false",
+    "null",
+    "undefined",
+];
+
+impl Keyword {
+    fn from_word(word: &str) -> Option<Self> {
+        let keyword = match word {
+            "fun" => Keyword::Fun,
+            "do" => Keyword::Do,
+            "end" => Keyword::End,
+            "if" => Keyword::If,
+            "elseif" => Keyword::Elseif,
+            "else" => Keyword::Else,
+            "while" => Keyword::While,
+            "module" => Keyword::Module,
+            "import" => Keyword::Import,
+            "as" => Keyword::As,
+            "return" => Keyword::Return,
+            "break" => Keyword::Break,
+            "continue" => Keyword::Continue,
+            "and" => Keyword::And,
+            "or" => Keyword::Or,
+            "not" => Keyword::Not,
+            "is" => Keyword::Is,
+            "true" => Keyword::True,
+            "false" => Keyword::False,
+            "null" => Keyword::Null,
+            "undefined" => Keyword::Undefined,
+            _ => return None,
+        };
+
+        Some(keyword)
+    }
+
+    /// Keywords that open a block closed by `end`.
+    pub fn opens_block(&self) -> bool {
+        matches!(
+            self,
+            Keyword::Fun | Keyword::If | Keyword::While | Keyword::Module
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Identifier,
+    Keyword(Keyword),
+    Integer,
+    Float,
+    String,
+    Plus,
+    Minus,
+    Star,
+    StarStar,
+    Slash,
+    SlashSlash,
+    Percent,
+    Assign,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Bang,
+    AndAnd,
+    OrOr,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    LeftBrace,
+    RightBrace,
+    Comma,
+    Dot,
+    Colon,
+    Eof,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte offsets in the source, `end` is exclusive.
+    pub start: usize,
+    pub end: usize,
+    /// Whether the token is the first of its line, used to resume after an error.
+    pub line_start: bool,
+}
+
+/// Splits a source into tokens, the last one is always [`TokenKind::Eof`]. Characters
+/// that start no token and broken literals are reported and skipped so the parser still
+/// sees the rest of the source.
+pub fn tokenize(context: &Context) -> (Vec<Token>, Vec<Error>) {
+    let mut lexer = Lexer {
+        context,
+        input: context.input.as_bytes(),
+        position: 0,
+        line_start: true,
+        tokens: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    lexer.run();
+
+    (lexer.tokens, lexer.errors)
+}
+
+struct Lexer<'a> {
+    context: &'a Context<'a>,
+    input: &'a [u8],
+    position: usize,
+    line_start: bool,
+    tokens: Vec<Token>,
+    errors: Vec<Error>,
+}
+
+impl Lexer<'_> {
+    fn run(&mut self) {
+        loop {
+            self.skip_trivia();
+
+            let start = self.position;
+
+            let Some(&byte) = self.input.get(start) else {
+                self.push(TokenKind::Eof, start);
+                return;
+            };
+
+            let kind = match byte {
+                b'"' => self.string(),
+                b'0'..=b'9' => self.number(),
+                b'.' if self.peek(1).is_some_and(|byte| byte.is_ascii_digit()) => self.number(),
+                b'_' | b'a'..=b'z' | b'A'..=b'Z' => match self.word() {
+                    Some(kind) => kind,
+                    None => continue,
+                },
+                _ => match self.symbol() {
+                    Some(kind) => kind,
+                    None => continue,
+                },
+            };
+
+            self.push(kind, start);
+        }
+    }
+
+    fn push(&mut self, kind: TokenKind, start: usize) {
+        self.tokens.push(Token {
+            kind,
+            start,
+            end: self.position,
+            line_start: self.line_start,
+        });
+
+        self.line_start = false;
+    }
+
+    fn peek(&self, ahead: usize) -> Option<u8> {
+        self.input.get(self.position + ahead).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        self.input[self.position..].starts_with(text.as_bytes())
+    }
+
+    /// Skips whitespace, `#` line comments and `#= ... =#` block comments, which nest.
+    fn skip_trivia(&mut self) {
+        while let Some(byte) = self.peek(0) {
+            match byte {
+                b'\n' => {
+                    self.line_start = true;
+                    self.position += 1;
+                }
+                b' ' | b'\t' | b'\r' => self.position += 1,
+                b'#' if self.peek(1) == Some(b'=') => self.block_comment(),
+                b'#' => {
+                    while self.peek(0).is_some_and(|byte| byte != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn block_comment(&mut self) {
+        let start = self.position;
+        let mut depth = 0;
+
+        while self.position < self.input.len() {
+            if self.starts_with("#=") {
+                depth += 1;
+                self.position += 2;
+            } else if self.starts_with("=#") {
+                depth -= 1;
+                self.position += 2;
+
+                if depth == 0 {
+                    return;
+                }
+            } else {
+                if self.peek(0) == Some(b'\n') {
+                    self.line_start = true;
+                }
+
+                self.position += 1;
+            }
+        }
+
+        self.errors.push(
+            error::syntax(self.context, start, start + 2, "unterminated block comment")
+                .with_label("comment started here")
+                .with_help("close it with `=#`"),
+        );
+    }
+
+    fn string(&mut self) -> TokenKind {
+        let start = self.position;
+
+        self.position += 1;
+
+        loop {
+            match self.peek(0) {
+                None => {
+                    self.errors.push(
+                        error::syntax(self.context, start, start + 1, "unterminated string")
+                            .with_label("string started here"),
+                    );
+
+                    return TokenKind::String;
+                }
+                Some(b'"') => {
+                    self.position += 1;
+
+                    return TokenKind::String;
+                }
+                Some(b'\\') => self.escape(),
+                Some(_) => self.position += 1,
+            }
+        }
+    }
+
+    fn escape(&mut self) {
+        let start = self.position;
+
+        match self.peek(1) {
+            Some(b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => self.position += 2,
+            Some(b'u')
+                if (2..6).all(|ahead| {
+                    self.peek(ahead)
+                        .is_some_and(|byte| byte.is_ascii_hexdigit())
+                }) =>
+            {
+                self.position += 6
+            }
+            Some(b'u') => {
+                self.position += 2;
+                self.errors.push(
+                    error::syntax(self.context, start, self.position, "invalid unicode escape")
+                        .with_label("expected 4 hexadecimal digits after `\\u`"),
+                );
+            }
+            _ => {
+                let escaped = self.context.input[start + 1..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+
+                self.position += 1 + escaped;
+                self.errors.push(
+                    error::syntax(
+                        self.context,
+                        start,
+                        self.position,
+                        "unknown escape sequence",
+                    )
+                    .with_label("unknown escape")
+                    .with_help("use `\\\\` to write a backslash"),
+                );
+            }
+        }
+    }
+
+    /// `0`, `12`, `-` is lexed on its own and joined to the number by the parser. A float
+    /// has digits after the dot and an optional exponent: `1.5`, `.5`, `2.0e-3`.
+    fn number(&mut self) -> TokenKind {
+        let digits = |lexer: &mut Self| {
+            while lexer.peek(0).is_some_and(|byte| byte.is_ascii_digit()) {
+                lexer.position += 1;
+            }
+        };
+
+        let start = self.position;
+
+        digits(self);
+
+        if self.peek(0) == Some(b'.') && self.peek(1).is_some_and(|byte| byte.is_ascii_digit()) {
+            self.position += 1;
+            digits(self);
+
+            let exponent = match (self.peek(0), self.peek(1), self.peek(2)) {
+                (Some(b'e' | b'E'), Some(b'+' | b'-'), Some(digit)) if digit.is_ascii_digit() => 2,
+                (Some(b'e' | b'E'), Some(digit), _) if digit.is_ascii_digit() => 1,
+                _ => 0,
+            };
+
+            if exponent > 0 {
+                self.position += exponent;
+                digits(self);
+            }
+
+            return TokenKind::Float;
+        }
+
+        // Like most languages, integers have no leading zeros: `01` is two numbers.
+        if self.input[start] == b'0' {
+            self.position = start + 1;
+        }
+
+        TokenKind::Integer
+    }
+
+    /// Identifiers are ASCII: optional leading underscores, a letter, then letters, digits
+    /// and underscores.
+    fn word(&mut self) -> Option<TokenKind> {
+        let start = self.position;
+
+        while self.peek(0) == Some(b'_') {
+            self.position += 1;
+        }
+
+        if !self.peek(0).is_some_and(|byte| byte.is_ascii_alphabetic()) {
+            while self
+                .peek(0)
+                .is_some_and(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+            {
+                self.position += 1;
+            }
+
+            self.errors.push(
+                error::syntax(self.context, start, self.position, "invalid name")
+                    .with_label("names must contain a letter before any digit"),
+            );
+
+            return None;
+        }
+
+        while self
+            .peek(0)
+            .is_some_and(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+        {
+            self.position += 1;
+        }
+
+        let word = &self.context.input[start..self.position];
+
+        Some(Keyword::from_word(word).map_or(TokenKind::Identifier, TokenKind::Keyword))
+    }
+
+    fn symbol(&mut self) -> Option<TokenKind> {
+        const SYMBOLS: &[(&str, TokenKind)] = &[
+            ("**", TokenKind::StarStar),
+            ("//", TokenKind::SlashSlash),
+            ("==", TokenKind::Equal),
+            ("~=", TokenKind::NotEqual),
+            ("!=", TokenKind::NotEqual),
+            ("<>", TokenKind::NotEqual),
+            ("<=", TokenKind::LessEqual),
+            (">=", TokenKind::GreaterEqual),
+            ("&&", TokenKind::AndAnd),
+            ("||", TokenKind::OrOr),
+            ("+", TokenKind::Plus),
+            ("-", TokenKind::Minus),
+            ("*", TokenKind::Star),
+            ("/", TokenKind::Slash),
+            ("%", TokenKind::Percent),
+            ("=", TokenKind::Assign),
+            ("<", TokenKind::Less),
+            (">", TokenKind::Greater),
+            ("!", TokenKind::Bang),
+            ("(", TokenKind::LeftParen),
+            (")", TokenKind::RightParen),
+            ("[", TokenKind::LeftBracket),
+            ("]", TokenKind::RightBracket),
+            ("{", TokenKind::LeftBrace),
+            ("}", TokenKind::RightBrace),
+            (",", TokenKind::Comma),
+            (".", TokenKind::Dot),
+            (":", TokenKind::Colon),
+        ];
+
+        for (symbol, kind) in SYMBOLS {
+            if self.starts_with(symbol) {
+                self.position += symbol.len();
+
+                return Some(*kind);
+            }
+        }
+
+        let start = self.position;
+        let char = self.context.input[start..].chars().next().unwrap();
+
+        self.position += char.len_utf8();
+        self.errors.push(
+            error::syntax(self.context, start, self.position, "unexpected character")
+                .with_label(&format!("`{}` is not valid here", char.escape_debug())),
+        );
+
+        None
+    }
+}
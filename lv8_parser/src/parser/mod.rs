@@ -1,26 +1,35 @@
+mod error;
 mod expression;
+mod lexer;
 mod statement;
 
-use pest::{error::LineColLocation, iterators::Pair, Parser};
 use std::collections::HashMap;
 
 use crate::Either;
-use lv8_common::error::{Error, Result, SyntaxError};
+use lv8_common::{
+    error::{Error, Result},
+    source::{FileId, LineIndex, Span},
+};
 
-#[derive(pest_derive::Parser)]
-#[grammar = "grammar/lv8.pest"]
-struct LV8Parser;
+pub use expression::unescape;
+pub use lexer::{Keyword, Token, TokenKind};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum ASTNode {
     Block(Block),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Block(pub Vec<Statement>, pub ReturnStatement);
 
-#[derive(Clone, Debug)]
-pub enum Statement {
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatementKind {
     Assignment {
         left: Vec<String>,
         right: Either<Expression, Box<Statement>>,
@@ -52,10 +61,24 @@ pub enum Statement {
         path: String,
         ident: String,
     },
+    /// A bare value like `1 + 2`, only parsed at the REPL prompt where it is shown.
+    Expression(Expression),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
 }
 
-#[derive(Clone, Debug)]
-pub enum Expression {
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpressionKind {
     Null,
     Undefined,
     Boolean(bool),
@@ -70,7 +93,7 @@ pub enum Expression {
     ComparisonExpression(ComparisonExpression),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum MathExpression {
     Number(Either<isize, f64>),
     Operation {
@@ -80,7 +103,7 @@ pub enum MathExpression {
     },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum MathOperation {
     Add,
     Subtract,
@@ -90,7 +113,7 @@ pub enum MathOperation {
     Exponentiation,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum LogicExpression {
     Not {
         expr: Box<Expression>,
@@ -106,14 +129,14 @@ pub enum LogicExpression {
     Boolean(bool),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ComparisonExpression {
     pub left: Box<Expression>,
     pub operation: ComparisonOperation,
     pub right: Box<Expression>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ComparisonOperation {
     Equal,
     NotEqual,
@@ -123,69 +146,372 @@ pub enum ComparisonOperation {
     LessThanOrEqual,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ReturnStatement {
     Return(Expression),
     Break,
     Continue,
 }
 
-pub fn parse(input: &str) -> Result<ASTNode> {
-    let mut pairs = match LV8Parser::parse(Rule::program, input) {
-        Ok(e) => e,
-        Err(e) => {
-            let (line, column) = match e.line_col {
-                LineColLocation::Pos((line, column)) => (line, column),
-                LineColLocation::Span((line, column), _) => (line, column),
-            };
+/// The source being parsed, turns byte offsets into [`Span`]s.
+struct Context<'a> {
+    file: FileId,
+    input: &'a str,
+    lines: LineIndex,
+}
+
+impl Context<'_> {
+    fn span(&self, start: usize, end: usize) -> Span {
+        let (line, column) = self.lines.line_col(self.input, start);
 
-            return Err(Error::syntax(
-                e.variant.to_string().as_str(),
-                SyntaxError::new(e.line(), line, column),
-            ));
+        Span {
+            file: self.file,
+            start: start as u32,
+            end: end as u32,
+            line: line as u32,
+            column: column as u32,
         }
+    }
+
+    fn text(&self, token: Token) -> &str {
+        &self.input[token.start..token.end]
+    }
+}
+
+/// Parses a whole source, `file` is the id the source was registered with in the
+/// [`lv8_common::source::SourceMap`] and ends up in the span of every node. Only the
+/// first syntax error is returned, see [`parse_recovering`] to get all of them.
+pub fn parse(input: &str, file: FileId) -> Result<ASTNode> {
+    let (ast, errors) = parse_recovering(input, file);
+
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(ast),
+    }
+}
+
+/// Parses a whole source and keeps going after syntax errors, for tools that want every
+/// error at once or an AST of a file being edited.
+///
+/// A statement that fails is left out of the AST and parsing resumes at the next line
+/// that starts a statement. The bodies of the blocks it opened are still parsed to
+/// report their errors. Errors are sorted by position.
+pub fn parse_recovering(input: &str, file: FileId) -> (ASTNode, Vec<Error>) {
+    parse_source(input, file, false)
+}
+
+/// Parses what was typed at the REPL prompt, which can also hold bare expressions and
+/// conditions like `x` or `1 < 2`.
+pub fn parse_interactive(input: &str, file: FileId) -> Result<ASTNode> {
+    let (ast, errors) = parse_source(input, file, true);
+
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(ast),
+    }
+}
+
+/// Whether REPL input only lacks its end, like an unclosed block, bracket or string, so
+/// more lines should be read before running it.
+pub fn is_incomplete(input: &str) -> bool {
+    let (_, errors) = parse_source(input, FileId::default(), true);
+
+    errors.iter().any(|error| {
+        error.message.starts_with("unterminated")
+            || error
+                .span
+                .is_some_and(|span| span.start as usize == input.len())
+    })
+}
+
+/// The tokens of a source without the final end of file, for highlighting. Comments
+/// and whitespace are left out, broken literals are kept and unknown characters are
+/// skipped.
+pub fn tokens(input: &str) -> Vec<Token> {
+    let context = Context {
+        file: FileId::default(),
+        input,
+        lines: LineIndex::new(input),
+    };
+
+    let (mut tokens, _) = lexer::tokenize(&context);
+
+    tokens.pop();
+
+    tokens
+}
+
+/// The keywords, which can't be used as names.
+pub fn keywords() -> impl Iterator<Item = &'static str> {
+    lexer::KEYWORDS.iter().copied()
+}
+
+fn parse_source(input: &str, file: FileId, interactive: bool) -> (ASTNode, Vec<Error>) {
+    let context = Context {
+        file,
+        input,
+        lines: LineIndex::new(input),
     };
 
-    Ok(ASTNode::Block(parse_block(
-        pairs.next().unwrap().into_inner().next().unwrap(),
-    )?))
-}
-
-fn parse_block(pair: Pair<Rule>) -> Result<Block> {
-    match pair.as_rule() {
-        Rule::block => {
-            let pair = pair.into_inner();
-
-            let mut statements = Vec::new();
-            let mut return_statement = ReturnStatement::Return(Expression::Undefined);
-
-            for pair in pair {
-                match pair.as_rule() {
-                    Rule::stmt => statements
-                        .push(statement::parse(pair.into_inner().next().unwrap()).unwrap()),
-                    Rule::return_statement => {
-                        let expr = pair.into_inner().next().unwrap();
-
-                        match expr.as_rule() {
-                            Rule::expr => {
-                                return_statement = ReturnStatement::Return(expression::parse(expr)?)
-                            }
-
-                            _ => {
-                                return_statement = match expr.as_str() {
-                                    "break" => ReturnStatement::Break,
-                                    "continue" => ReturnStatement::Continue,
-                                    _ => unreachable!("Unknown rule: {:?}", expr.as_rule()),
-                                }
-                            }
+    let (tokens, mut errors) = lexer::tokenize(&context);
+
+    let mut parser = Parser {
+        context: &context,
+        tokens,
+        position: 0,
+        errors: Vec::new(),
+        interactive,
+    };
+
+    let block = parser.block(&[]);
+
+    errors.append(&mut parser.errors);
+    errors.sort_by_key(|error| error.span.map(|span| span.start));
+
+    (ASTNode::Block(block), errors)
+}
+
+struct Parser<'a> {
+    context: &'a Context<'a>,
+    tokens: Vec<Token>,
+    position: usize,
+    /// Errors of the statements that were skipped.
+    errors: Vec<Error>,
+    /// Whether expressions can be statements, at the REPL prompt.
+    interactive: bool,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Token {
+        self.nth(0)
+    }
+
+    /// The token `ahead` tokens after the current one, [`TokenKind::Eof`] past the end.
+    fn nth(&self, ahead: usize) -> Token {
+        let last = self.tokens.len() - 1;
+
+        self.tokens[(self.position + ahead).min(last)]
+    }
+
+    fn at(&self, kind: TokenKind) -> bool {
+        self.peek().kind == kind
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek();
+
+        if token.kind != TokenKind::Eof {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> Option<Token> {
+        self.at(kind).then(|| self.advance())
+    }
+
+    /// Consumes a token of `kind`, `what` describes it in the error otherwise.
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Result<Token> {
+        match self.eat(kind) {
+            Some(token) => Ok(token),
+            None => Err(self.expected(what)),
+        }
+    }
+
+    /// An error for the current token.
+    fn expected(&self, what: &str) -> Error {
+        error::expected(self.context, self.peek(), what)
+    }
+
+    /// The end of the last consumed token.
+    fn previous_end(&self) -> usize {
+        match self.position {
+            0 => 0,
+            position => self.tokens[position - 1].end,
+        }
+    }
+
+    /// The span from the start of `start` to the last consumed token.
+    fn span_from(&self, start: Token) -> Span {
+        self.context.span(start.start, self.previous_end())
+    }
+
+    fn text(&self, token: Token) -> &str {
+        self.context.text(token)
+    }
+
+    /// Statements up to one of the `closers` keywords or the end of the file, which are
+    /// left for the caller. A block without `return` returns `undefined`, located where
+    /// the block ends.
+    fn block(&mut self, closers: &[Keyword]) -> Block {
+        let mut statements = Vec::new();
+        let mut return_statement = None;
+
+        loop {
+            let token = self.peek();
+            let start = self.position;
+
+            match token.kind {
+                TokenKind::Eof => break,
+                TokenKind::Keyword(keyword) if closers.contains(&keyword) => break,
+                TokenKind::Keyword(Keyword::Return | Keyword::Break | Keyword::Continue) => {
+                    match self.return_statement() {
+                        Ok(statement) => {
+                            return_statement.get_or_insert(statement);
+                        }
+                        Err(error) => {
+                            self.errors.push(error);
+                            self.synchronize(start);
+                            continue;
+                        }
+                    }
+
+                    let next = self.peek();
+                    let closes = match next.kind {
+                        TokenKind::Eof => true,
+                        TokenKind::Keyword(keyword) => closers.contains(&keyword),
+                        _ => false,
+                    };
+
+                    if closes {
+                        continue;
+                    }
+
+                    let error = if next.line_start {
+                        error::syntax(
+                            self.context,
+                            next.start,
+                            next.end,
+                            &format!(
+                                "`{}` must be the last statement of a block",
+                                self.text(token)
+                            ),
+                        )
+                        .with_label("unreachable statement")
+                    } else {
+                        self.expected(&describe_closers(closers))
+                    };
+
+                    self.errors.push(error);
+
+                    if !next.line_start {
+                        self.advance();
+                        self.synchronize(start);
+                    }
+                }
+                _ if starts_statement(token.kind) || self.at_expression_statement() => {
+                    match self.statement() {
+                        // Statements after a `return` are only parsed to report their errors.
+                        Ok(statement) if return_statement.is_none() => statements.push(statement),
+                        Ok(_) => {}
+                        Err(error) => {
+                            self.errors.push(error);
+                            self.synchronize(start);
                         }
                     }
-                    _ => unreachable!("Unknown rule: {:?}", pair.as_rule()),
                 }
+                _ => {
+                    self.errors.push(self.expected("a statement"));
+                    self.advance();
+                    self.synchronize(start);
+                }
+            }
+        }
+
+        let end = self.peek().start;
+        let return_statement = return_statement.unwrap_or_else(|| {
+            ReturnStatement::Return(Expression::new(
+                ExpressionKind::Undefined,
+                self.context.span(end, end),
+            ))
+        });
+
+        Block(statements, return_statement)
+    }
+
+    /// Skips what is left of a statement that failed to parse, the statement started at
+    /// token `start`. Parsing resumes at the next line, or at an `end`, `else` or `elseif`
+    /// that closes the enclosing block.
+    fn synchronize(&mut self, start: usize) {
+        let mut depth = open_blocks(&self.tokens[start..self.position]);
+
+        if self.position == start {
+            self.advance();
+        }
+
+        while !self.at(TokenKind::Eof) && !self.peek().line_start {
+            match self.peek().kind {
+                TokenKind::Keyword(Keyword::End | Keyword::Else | Keyword::Elseif)
+                    if depth == 0 =>
+                {
+                    return
+                }
+                TokenKind::Keyword(Keyword::End) => depth -= 1,
+                TokenKind::Keyword(keyword) if keyword.opens_block() => depth += 1,
+                _ => {}
+            }
+
+            self.advance();
+        }
+
+        // The blocks the statement opened, their statements are checked but dropped.
+        while depth > 0 {
+            self.block(&[Keyword::End, Keyword::Else, Keyword::Elseif]);
+
+            match self.advance().kind {
+                TokenKind::Keyword(Keyword::End) => depth -= 1,
+                TokenKind::Keyword(_) => {
+                    while !self.at(TokenKind::Eof) && !self.peek().line_start {
+                        self.advance();
+                    }
+                }
+                _ => return,
             }
+        }
+    }
+
+    /// `return`, `return value`, `break` or `continue`.
+    fn return_statement(&mut self) -> Result<ReturnStatement> {
+        let token = self.advance();
 
-            Ok(Block(statements, return_statement))
+        match token.kind {
+            TokenKind::Keyword(Keyword::Break) => Ok(ReturnStatement::Break),
+            TokenKind::Keyword(Keyword::Continue) => Ok(ReturnStatement::Continue),
+            _ if expression::starts_expression(self.peek().kind) => {
+                Ok(ReturnStatement::Return(self.expression()?))
+            }
+            _ => Ok(ReturnStatement::Return(Expression::new(
+                ExpressionKind::Undefined,
+                self.span_from(token),
+            ))),
         }
-        _ => unreachable!("Unknown rule: {:?}", pair.as_rule()),
+    }
+}
+
+fn starts_statement(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Identifier
+            | TokenKind::Keyword(
+                Keyword::Fun | Keyword::If | Keyword::While | Keyword::Module | Keyword::Import
+            )
+    )
+}
+
+/// How many blocks `tokens` open without closing them.
+fn open_blocks(tokens: &[Token]) -> usize {
+    tokens.iter().fold(0, |depth, token| match token.kind {
+        TokenKind::Keyword(Keyword::End) => depth.saturating_sub(1),
+        TokenKind::Keyword(keyword) if keyword.opens_block() => depth + 1,
+        _ => depth,
+    })
+}
+
+fn describe_closers(closers: &[Keyword]) -> String {
+    match closers {
+        [] => "end of file".to_string(),
+        [_] => "`end`".to_string(),
+        _ => "`end`, `else` or `elseif`".to_string(),
     }
 }
@@ -1,170 +1,281 @@
-use pest::iterators::Pair;
-
 use crate::Either;
 
-use super::{expression, Rule, Statement};
+use super::{
+    error,
+    expression::{starts_expression, string_value},
+    Expression, ExpressionKind, Keyword, Parser, Statement, StatementKind, Token, TokenKind,
+};
 
 use lv8_common::error::Result;
 
-pub fn parse(pair: Pair<Rule>) -> Result<Statement> {
-    match pair.as_rule() {
-        Rule::assign => {
-            let mut inner = pair.into_inner();
+impl Parser<'_> {
+    pub(super) fn statement(&mut self) -> Result<Statement> {
+        let start = self.peek();
+
+        let kind = match start.kind {
+            TokenKind::Keyword(Keyword::Module) => self.module_definition()?,
+            TokenKind::Keyword(Keyword::Import) => self.import()?,
+            TokenKind::Keyword(Keyword::While) => self.while_statement()?,
+            TokenKind::Keyword(Keyword::If) => self.if_statement()?,
+            TokenKind::Keyword(Keyword::Fun) => self.function_definition()?,
+            TokenKind::Identifier
+                if matches!(self.nth(1).kind, TokenKind::Comma | TokenKind::Assign) =>
+            {
+                self.assignment()?
+            }
+            TokenKind::Identifier if !self.interactive || self.at_function_call() => {
+                self.function_call()?
+            }
+            _ if self.at_expression_statement() => StatementKind::Expression(self.condition()?),
+            _ => return Err(self.expected("a statement")),
+        };
+
+        Ok(Statement {
+            kind,
+            span: self.span_from(start),
+        })
+    }
 
-            let var_list = inner.next().unwrap();
-            let expr = inner.next().unwrap();
+    /// The right side of an assignment or an argument, statements come first so
+    /// `x = f(1)` is a call rather than the name `f` followed by garbage.
+    fn statement_or_expression(&mut self) -> Result<Either<Expression, Statement>> {
+        let is_statement = match self.peek().kind {
+            TokenKind::Keyword(keyword) => matches!(
+                keyword,
+                Keyword::Module | Keyword::Import | Keyword::While | Keyword::If | Keyword::Fun
+            ),
+            TokenKind::Identifier => self.at_assignment() || self.at_function_call(),
+            _ => false,
+        };
+
+        if is_statement {
+            Ok(Either::Right(self.statement()?))
+        } else {
+            Ok(Either::Left(self.expression()?))
+        }
+    }
 
-            let mut left: Vec<String> = vec![];
+    /// A bare expression ahead, which is a statement at the REPL prompt. Names are,
+    /// unless they are called or assigned.
+    pub(super) fn at_expression_statement(&self) -> bool {
+        if !self.interactive {
+            return false;
+        }
 
-            for var in var_list.into_inner() {
-                left.push(var.as_str().to_string());
+        match self.peek().kind {
+            TokenKind::Identifier => {
+                !matches!(self.nth(1).kind, TokenKind::Comma | TokenKind::Assign)
+                    && !self.at_function_call()
             }
+            TokenKind::Keyword(Keyword::Not) | TokenKind::Bang => true,
+            kind => starts_expression(kind),
+        }
+    }
 
-            let right = match expr.as_rule() {
-                Rule::expr => {
-                    Either::Left(super::expression::parse(expr.into_inner().next().unwrap())?)
-                }
-                Rule::stmt => Either::Right(Box::new(parse(expr.into_inner().next().unwrap())?)),
-                _ => unreachable!("unreachable!() in statement.rs, {:?}", expr.as_rule()),
-            };
+    /// `a, b =` ahead.
+    fn at_assignment(&self) -> bool {
+        let mut ahead = 0;
+
+        loop {
+            if self.nth(ahead).kind != TokenKind::Identifier {
+                return false;
+            }
+
+            match self.nth(ahead + 1).kind {
+                TokenKind::Assign => return true,
+                TokenKind::Comma => ahead += 2,
+                _ => return false,
+            }
+        }
+    }
 
-            Ok(Statement::Assignment { left, right })
+    /// `name(` or `Module.name(` ahead.
+    fn at_function_call(&self) -> bool {
+        let mut ahead = 1;
+
+        while self.nth(ahead).kind == TokenKind::Dot && is_word(self.nth(ahead + 1).kind) {
+            ahead += 2;
         }
 
-        Rule::function_call => {
-            let mut inner = pair.into_inner();
+        self.nth(ahead).kind == TokenKind::LeftParen
+    }
+
+    fn assignment(&mut self) -> Result<StatementKind> {
+        let left = self.identifier_list("a name")?;
+
+        self.expect(TokenKind::Assign, "`=`")?;
 
-            let expression = inner.next().unwrap();
-            let expression = expression::parse(expression)?;
+        let right = match self.statement_or_expression()? {
+            Either::Left(expression) => Either::Left(expression),
+            Either::Right(statement) => Either::Right(Box::new(statement)),
+        };
+
+        Ok(StatementKind::Assignment { left, right })
+    }
 
-            let mut args = Vec::new();
+    fn function_call(&mut self) -> Result<StatementKind> {
+        let expression = self.name()?;
 
-            for pair in inner {
-                match pair.as_rule() {
-                    Rule::expr => args.push(Either::Left(super::expression::parse(
-                        pair.into_inner().next().unwrap(),
-                    )?)),
-                    Rule::stmt => {
-                        args.push(Either::Right(parse(pair.into_inner().next().unwrap())?))
-                    }
-                    _ => unreachable!("unreachable!() in statement.rs, {:?}", pair.as_rule()),
+        let what = match expression.kind {
+            ExpressionKind::Identifier(_) => "`=` or `(`",
+            _ => "`(`",
+        };
+
+        self.expect(TokenKind::LeftParen, what)?;
+
+        let mut arguments = Vec::new();
+
+        if self.eat(TokenKind::RightParen).is_none() {
+            loop {
+                arguments.push(self.statement_or_expression()?);
+
+                if self.eat(TokenKind::Comma).is_none() {
+                    self.expect(TokenKind::RightParen, "`,` or `)`")?;
+                    break;
                 }
             }
-
-            Ok(Statement::FunctionCall {
-                expression,
-                arguments: args,
-            })
         }
 
-        Rule::function_def => {
-            let mut pairs = pair.into_inner();
+        Ok(StatementKind::FunctionCall {
+            expression,
+            arguments,
+        })
+    }
 
-            let name = pairs.next().unwrap().as_str().to_string();
+    fn function_definition(&mut self) -> Result<StatementKind> {
+        let keyword = self.advance();
+        let name = self.identifier("a function name")?;
 
-            let mut params = vec![];
+        self.expect(TokenKind::LeftParen, "`(`")?;
 
-            let body_or_params = pairs.next().unwrap();
+        let parameters = if self.eat(TokenKind::RightParen).is_some() {
+            Vec::new()
+        } else {
+            let parameters = self.identifier_list("a parameter name or `)`")?;
 
-            let body = match body_or_params.as_rule() {
-                Rule::ident_list => {
-                    params = body_or_params
-                        .into_inner()
-                        .map(|x| x.as_str().to_string())
-                        .collect::<Vec<String>>();
+            self.expect(TokenKind::RightParen, "`,` or `)`")?;
 
-                    super::parse_block(pairs.next().unwrap())?
-                }
-                Rule::block => super::parse_block(body_or_params)?,
-                _ => unreachable!(
-                    "unreachable!() in statement.rs, {:?}",
-                    body_or_params.as_rule()
-                ),
-            };
+            parameters
+        };
 
-            Ok(Statement::FunctionDefinition {
-                name,
-                parameters: params,
-                body,
-            })
-        }
+        self.expect(TokenKind::Keyword(Keyword::Do), "`do`")?;
 
-        Rule::if_statement => {
-            let mut pairs = pair.into_inner();
+        let body = self.block(&[Keyword::End]);
 
-            let condition =
-                super::expression::parse(pairs.next().unwrap().into_inner().next().unwrap())?;
+        self.expect_end(keyword);
 
-            let body = super::parse_block(pairs.next().unwrap())?;
+        Ok(StatementKind::FunctionDefinition {
+            name,
+            parameters,
+            body,
+        })
+    }
 
-            let mut else_if = Vec::new();
-            let mut else_body = None;
+    fn if_statement(&mut self) -> Result<StatementKind> {
+        let keyword = self.advance();
+        let condition = self.condition()?;
 
-            while let Some(pair) = pairs.next() {
-                match pair.as_rule() {
-                    // else if statement
-                    Rule::logic_expr => {
-                        let condition = super::expression::parse(pair.clone())?;
-                        let body = super::parse_block(pairs.next().unwrap())?;
+        self.expect(TokenKind::Keyword(Keyword::Do), "`do`")?;
 
-                        else_if.push((condition, body));
-                    }
+        let closers = [Keyword::End, Keyword::Else, Keyword::Elseif];
+        let body = self.block(&closers);
 
-                    // else statement
-                    Rule::block => {
-                        let body = super::parse_block(pair)?;
+        let mut else_if = Vec::new();
+        let mut else_body = None;
 
-                        else_body = Some(body);
-                    }
+        while self.eat(TokenKind::Keyword(Keyword::Elseif)).is_some() {
+            let condition = self.condition()?;
 
-                    _ => unreachable!("unreachable!() in statement.rs, {:?}", pair.as_rule()),
-                }
-            }
+            self.expect(TokenKind::Keyword(Keyword::Do), "`do`")?;
 
-            Ok(Statement::If {
-                condition,
-                body,
-                else_if,
-                else_body,
-            })
+            else_if.push((condition, self.block(&closers)));
         }
 
-        Rule::while_statement => {
-            let mut pairs = pair.into_inner();
+        if self.eat(TokenKind::Keyword(Keyword::Else)).is_some() {
+            else_body = Some(self.block(&[Keyword::End]));
+        }
 
-            let condition =
-                super::expression::parse(pairs.next().unwrap().into_inner().next().unwrap())?;
+        self.expect_end(keyword);
 
-            let body = super::parse_block(pairs.next().unwrap())?;
+        Ok(StatementKind::If {
+            condition,
+            body,
+            else_if,
+            else_body,
+        })
+    }
 
-            Ok(Statement::While { condition, body })
-        }
+    fn while_statement(&mut self) -> Result<StatementKind> {
+        let keyword = self.advance();
+        let condition = self.condition()?;
+
+        self.expect(TokenKind::Keyword(Keyword::Do), "`do`")?;
+
+        let body = self.block(&[Keyword::End]);
+
+        self.expect_end(keyword);
+
+        Ok(StatementKind::While { condition, body })
+    }
+
+    fn module_definition(&mut self) -> Result<StatementKind> {
+        let keyword = self.advance();
+        let name = self.identifier("a module name")?;
+
+        self.expect(TokenKind::Keyword(Keyword::Do), "`do`")?;
 
-        Rule::module_def => {
-            let mut pairs = pair.into_inner();
+        let body = self.block(&[Keyword::End]);
 
-            let name = pairs.next().unwrap().as_str().to_string();
-            let body = super::parse_block(pairs.next().unwrap())?;
+        self.expect_end(keyword);
 
-            Ok(Statement::ModuleDefinition { name, body })
+        Ok(StatementKind::ModuleDefinition { name, body })
+    }
+
+    fn import(&mut self) -> Result<StatementKind> {
+        self.advance();
+
+        let path = self.expect(TokenKind::String, "a path string")?;
+        let path = string_value(self.text(path));
+
+        self.expect(TokenKind::Keyword(Keyword::As), "`as`")?;
+
+        let ident = self.identifier("a name")?;
+
+        Ok(StatementKind::Import { path, ident })
+    }
+
+    /// The `end` of the block opened by `keyword`. A missing `end` is reported but the
+    /// statement is kept, so a file being written still has an AST.
+    fn expect_end(&mut self, keyword: Token) {
+        if self.eat(TokenKind::Keyword(Keyword::End)).is_none() {
+            let error = match self.peek().kind {
+                TokenKind::Eof => error::unclosed(self.context, self.peek(), keyword),
+                _ => self.expected("`end`"),
+            };
+
+            self.errors.push(error);
         }
+    }
 
-        Rule::import_statement => {
-            let mut pairs = pair.into_inner();
+    fn identifier(&mut self, what: &str) -> Result<String> {
+        let token = self.expect(TokenKind::Identifier, what)?;
 
-            let path = pairs
-                .next()
-                .unwrap()
-                .into_inner()
-                .next()
-                .unwrap()
-                .as_str()
-                .to_string();
-            let ident = pairs.next().unwrap().as_str().to_string();
+        Ok(self.text(token).to_string())
+    }
 
-            Ok(Statement::Import { path, ident })
+    fn identifier_list(&mut self, what: &str) -> Result<Vec<String>> {
+        let mut identifiers = vec![self.identifier(what)?];
+
+        while self.eat(TokenKind::Comma).is_some() {
+            identifiers.push(self.identifier("a name")?);
         }
 
-        _ => unreachable!("unreachable!() in statement.rs, {:?}", pair.as_rule()),
+        Ok(identifiers)
     }
 }
+
+/// Identifiers and keywords, members and object keys can be any word so `match.end`
+/// works.
+pub(super) fn is_word(kind: TokenKind) -> bool {
+    matches!(kind, TokenKind::Identifier | TokenKind::Keyword(_))
+}
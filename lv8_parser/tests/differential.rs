@@ -0,0 +1,656 @@
+//! Checks the parser against the pest grammar it replaced: every program the grammar
+//! accepts must give the same AST, spans included. The places where the two are meant
+//! to differ are tested on their own at the end.
+
+mod reference;
+
+use std::fs;
+
+use lv8_common::{error::Error, source::FileId};
+use lv8_parser::{
+    ASTNode, Block, Expression, ExpressionKind, ReturnStatement, Statement, StatementKind,
+};
+
+fn parse(input: &str) -> (ASTNode, Vec<Error>) {
+    lv8_parser::parse_recovering(input, FileId::default())
+}
+
+fn messages(errors: &[Error]) -> Vec<String> {
+    errors.iter().map(|error| error.message.clone()).collect()
+}
+
+fn statements(ast: &ASTNode) -> &Block {
+    let ASTNode::Block(block) = ast;
+
+    block
+}
+
+fn assert_same(input: &str) {
+    let expected = reference::parse(input)
+        .unwrap_or_else(|| panic!("the grammar rejects the program:\n{}", input));
+    let (ast, errors) = parse(input);
+
+    assert!(
+        errors.is_empty(),
+        "the parser rejects the program:\n{}\n{:?}",
+        input,
+        messages(&errors)
+    );
+    if ast != expected {
+        panic!(
+            "different ASTs for the program:\n{}\n{}",
+            input,
+            first_difference(&format!("{:#?}", ast), &format!("{:#?}", expected))
+        );
+    }
+}
+
+/// The first line where two pretty printed ASTs differ, with the lines before it.
+fn first_difference(ast: &str, expected: &str) -> String {
+    let (ast, expected): (Vec<&str>, Vec<&str>) =
+        (ast.lines().collect(), expected.lines().collect());
+    let index = ast
+        .iter()
+        .zip(&expected)
+        .position(|(left, right)| left != right)
+        .unwrap_or(ast.len().min(expected.len()));
+    let context = ast[index.saturating_sub(8)..index].join("\n");
+
+    format!(
+        "{}\n  parser: {}\n grammar: {}",
+        context,
+        ast.get(index).unwrap_or(&"<end>"),
+        expected.get(index).unwrap_or(&"<end>")
+    )
+}
+
+fn assert_rejected(input: &str) {
+    assert!(
+        reference::parse(input).is_none(),
+        "the grammar accepts the program:\n{}",
+        input
+    );
+    assert!(
+        !parse(input).1.is_empty(),
+        "the parser accepts the program:\n{}",
+        input
+    );
+}
+
+const PROGRAMS: &[&str] = &[
+    "",
+    "# only a comment\n",
+    "x = 1",
+    "x = -42\ny = 0\nz = 3.25\nw = -.5\nv = 1.5e3\nu = 2.0E-2",
+    "a, b, c = 1",
+    "x = y = 3",
+    "_private = null\nnothing = undefined\nyes, no = true\nx = false",
+    r#"s = "escapes \" \\ \/ \b \f \n \r \t \u00e9""#,
+    r#"s = """#,
+    "x = [] y = [1, -2, [3], {}]",
+    r#"o = {a: 1, "b c": [2], d: {e: "f"}, a: 2}"#,
+    "x = 1 + 2 * 3 - 4 / 5 % 6",
+    "x = 2 ** 3 ** 2",
+    "x = (1 + 2) * (3 - x) ** (y / 2)",
+    "x = a - -1\ny = b -1\nz = 1 -1",
+    "x = Math.pi\ny = a . b . c",
+    "print()\nprint(1, \"two\", x, [3])\nMath.pow(x, 2)",
+    "print(f(g(1)), h())",
+    "x = f(1)",
+    "x = fun f() do end",
+    "print(x = 1)",
+    "fun f() do end",
+    "fun f(a, b) do\n  return a + b\nend",
+    "fun f(a) do\n  print(a)\n  return a * (a + 1)\nend",
+    "fun f() do\n  # nothing\nend\n\n",
+    "if x do\n  print(1)\nend",
+    "if (x > 1) do print(1) elseif x < 2 do print(2) else print(3) end",
+    "if x == 1 and y ~= 2 or not z do end",
+    "if not (a and b) || !c && d is e do end",
+    "if a + 1 > b * 2 do end",
+    "if a == not b == c do end",
+    "if (a) do end",
+    "if \"a\" != [1] do end",
+    "while i <= 100 do\n  i = i + 1\n  if i > 50 do\n    break\n  end\nend",
+    "while (i < 10) do\n  continue\nend",
+    "module Shapes do\n  fun area(side) do\n    return side ** 2\n  end\nend",
+    "import \"shapes.lv\" as Shapes\nprint(Shapes.area(2))",
+    "return 1",
+    "x = 1\nreturn x",
+];
+
+#[test]
+fn same_ast_as_the_grammar() {
+    for program in PROGRAMS {
+        assert_same(program);
+    }
+}
+
+#[test]
+fn same_ast_for_the_examples() {
+    let examples = concat!(env!("CARGO_MANIFEST_DIR"), "/../examples");
+
+    for entry in fs::read_dir(examples).unwrap() {
+        assert_same(&fs::read_to_string(entry.unwrap().path()).unwrap());
+    }
+}
+
+#[test]
+fn same_errors_as_the_grammar() {
+    for program in [
+        "x =",
+        "x = 1 +",
+        "x = (1 + 2",
+        "x = [1, 2",
+        "x = {a 1}",
+        "x = \"a\" + 1",
+        "f(1",
+        "f(1,)",
+        "a.b = 1",
+        "1 = x",
+        "fun (a) do end",
+        "fun f(a do end",
+        "fun f() print(1) end",
+        "fun f() do",
+        "if x do",
+        "if x print(1) end",
+        "if x do else print(1) elseif y do end",
+        "while do end",
+        "module do end",
+        "import shapes as Shapes",
+        "import \"shapes\" Shapes",
+        "return 1\nx = 2",
+        "x = f(1) + 2",
+        "x = \"unterminated",
+        "x = \"\\q\"",
+        "x = 1 @ 2",
+        "x = not y",
+        "if x > do end",
+        "if (x > 1) + 1 do end",
+        "end",
+        "else",
+    ] {
+        assert_rejected(program);
+    }
+}
+
+/// Random programs in the language both parsers accept, from a fixed seed so failures
+/// can be reproduced.
+struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    fn next(&mut self) -> u64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn name(&mut self) -> String {
+        self.pick(&["x", "y", "value", "total", "_private", "alpha2", "beta_b"])
+            .to_string()
+    }
+
+    fn path(&mut self) -> String {
+        let module = self.pick(&["Math", "Shapes", "OS"]);
+        let member = self.pick(&["pow", "area", "args", "end"]);
+
+        format!("{}.{}", module, member)
+    }
+
+    fn number(&mut self) -> String {
+        self.pick(&[
+            "0", "7", "-3", "42", "1.5", "-0.25", ".5", "2.5e3", "-1.0E-2",
+        ])
+        .to_string()
+    }
+
+    fn operand(&mut self, depth: usize) -> String {
+        match self.below(if depth == 0 { 2 } else { 3 }) {
+            0 => self.number(),
+            1 => self.name(),
+            _ => format!("({})", self.math(depth - 1)),
+        }
+    }
+
+    fn math(&mut self, depth: usize) -> String {
+        let mut math = self.operand(depth);
+
+        for _ in 0..=self.below(3) {
+            let operator = self.pick(&["+", "-", "*", "/", "%", "**"]);
+
+            math = format!("{} {} {}", math, operator, self.operand(depth));
+        }
+
+        math
+    }
+
+    fn expression(&mut self, depth: usize) -> String {
+        match self.below(if depth == 0 { 6 } else { 9 }) {
+            0 => self.number(),
+            1 => self.name(),
+            2 => self.path(),
+            3 => self
+                .pick(&["null", "undefined", "true", "false"])
+                .to_string(),
+            4 => self
+                .pick(&[r#""""#, r#""hello""#, r#""a\nb""#, r#""\"q\" \u00e9""#])
+                .to_string(),
+            5 => self.math(0),
+            6 => self.math(depth),
+            7 => {
+                let items: Vec<String> = (0..self.below(4))
+                    .map(|_| self.expression(depth - 1))
+                    .collect();
+
+                format!("[{}]", items.join(", "))
+            }
+            _ => {
+                let pairs: Vec<String> = (0..self.below(4))
+                    .map(|_| {
+                        let key = match self.below(2) {
+                            0 => self.name(),
+                            _ => "\"some key\"".to_string(),
+                        };
+
+                        format!("{}: {}", key, self.expression(depth - 1))
+                    })
+                    .collect();
+
+                format!("{{{}}}", pairs.join(", "))
+            }
+        }
+    }
+
+    /// A value for a condition, the grammar reads a leading `(` as grouping a condition
+    /// so it rejects `(a + 1) * 2` there.
+    fn condition_value(&mut self, depth: usize) -> String {
+        loop {
+            let value = self.expression(depth);
+
+            if !value.starts_with('(') {
+                return value;
+            }
+        }
+    }
+
+    fn term(&mut self, depth: usize) -> String {
+        match self.below(if depth == 0 { 3 } else { 6 }) {
+            0 => self.condition_value(depth),
+            1 => format!("not {}", self.condition_value(depth)),
+            2 => format!("!{}", self.condition_value(depth)),
+            3 => format!("({})", self.condition(depth - 1)),
+            4 => format!("not ({})", self.condition(depth - 1)),
+            _ => format!("!({})", self.condition(depth - 1)),
+        }
+    }
+
+    fn condition(&mut self, depth: usize) -> String {
+        let mut condition = self.term(depth);
+
+        for _ in 0..self.below(4) {
+            let operator = self.pick(&[
+                "and", "or", "&&", "||", "==", "is", "!=", "~=", "<", "<=", ">",
+            ]);
+
+            condition = format!("{} {} {}", condition, operator, self.term(depth));
+        }
+
+        condition
+    }
+
+    fn call(&mut self, depth: usize) -> String {
+        let callee = match self.below(2) {
+            0 => self.name(),
+            _ => self.path(),
+        };
+        let arguments: Vec<String> = (0..self.below(4))
+            .map(|_| match self.below(4) {
+                0 if depth > 0 => self.call(depth - 1),
+                _ => self.expression(depth),
+            })
+            .collect();
+
+        format!("{}({})", callee, arguments.join(", "))
+    }
+
+    fn block(&mut self, depth: usize, indent: usize, in_loop: bool) -> String {
+        let mut lines: Vec<String> = (0..self.below(4))
+            .map(|_| self.statement(depth, indent, in_loop))
+            .collect();
+
+        match self.below(4) {
+            0 => lines.push(format!("return {}", self.expression(depth))),
+            1 if in_loop => lines.push(self.pick(&["break", "continue"]).to_string()),
+            _ => {}
+        }
+
+        let separator = format!("\n{}", "  ".repeat(indent));
+
+        format!("{}{}", separator, lines.join(&separator))
+    }
+
+    fn statement(&mut self, depth: usize, indent: usize, in_loop: bool) -> String {
+        let inner = indent + 1;
+        let end = format!("\n{}end", "  ".repeat(indent));
+
+        match self.below(if depth == 0 { 4 } else { 9 }) {
+            0 => format!("{} = {}", self.name(), self.expression(depth)),
+            1 => format!("{}, {} = {}", self.name(), self.name(), self.call(depth)),
+            2 => self.call(depth),
+            3 => format!("# {}", self.name()),
+            4 => format!(
+                "fun {}({}) do{}{}",
+                self.name(),
+                ["", "a", "a, b"][self.below(3)],
+                self.block(depth - 1, inner, false),
+                end
+            ),
+            5 => {
+                let mut statement = format!(
+                    "if {} do{}",
+                    self.condition(depth),
+                    self.block(depth - 1, inner, in_loop)
+                );
+
+                for _ in 0..self.below(3) {
+                    // The grammar reads `elseif (` as a call to a function named `elseif`.
+                    let condition = self.condition(depth - 1);
+                    let condition = match condition.starts_with('(') {
+                        true => format!("x and {}", condition),
+                        false => condition,
+                    };
+
+                    statement += &format!(
+                        "\n{}elseif {} do{}",
+                        "  ".repeat(indent),
+                        condition,
+                        self.block(depth - 1, inner, in_loop)
+                    );
+                }
+
+                if self.below(2) == 0 {
+                    statement += &format!(
+                        "\n{}else{}",
+                        "  ".repeat(indent),
+                        self.block(depth - 1, inner, in_loop)
+                    );
+                }
+
+                statement + &end
+            }
+            6 => format!(
+                "while {} do{}{}",
+                self.condition(depth),
+                self.block(depth - 1, inner, true),
+                end
+            ),
+            7 => format!(
+                "module Shapes do{}{}",
+                self.block(depth - 1, inner, false),
+                end
+            ),
+            _ => format!("import \"lib/{}.lv\" as Lib", self.name()),
+        }
+    }
+}
+
+#[test]
+fn same_ast_for_generated_programs() {
+    let mut generator = Generator {
+        state: 0x2545_f491_4f6c_dd1d,
+    };
+
+    let mut compared = 0;
+
+    for _ in 0..500 {
+        let program = generator.block(3, 0, false);
+
+        // Some quirks of the grammar are easier to skip than to avoid generating, like
+        // `not (a) * 2` in a condition.
+        if reference::parse(&program).is_some() {
+            assert_same(&program);
+            compared += 1;
+        }
+    }
+
+    assert!(compared > 400, "only {} programs compared", compared);
+}
+
+// Where the parser is meant to differ from the grammar.
+
+#[test]
+fn keywords_are_reserved() {
+    for program in [
+        "end = 1",
+        "if = 2",
+        "x = return",
+        "fun do() do end",
+        "fun f(end) do end",
+        "not(1)",
+        "if a do\nelseif(b)\nend",
+    ] {
+        assert!(reference::parse(program).is_some(), "{}", program);
+        assert!(!parse(program).1.is_empty(), "{}", program);
+    }
+
+    // Members and keys can still be any word.
+    let (_, errors) = parse("x = found.end\ny = {end: 1, if: 2}");
+
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+}
+
+#[test]
+fn names_starting_with_keywords() {
+    let (ast, errors) = parse("x = nullable\nif notice do end");
+
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+    assert_eq!(statements(&ast).0.len(), 2);
+}
+
+#[test]
+fn operators_the_grammar_cannot_parse() {
+    for program in [
+        "if a >= b do end",
+        "if a <> b do end",
+        "if (a + 1) * 2 > b do end",
+    ] {
+        assert!(reference::parse(program).is_none(), "{}", program);
+        assert!(parse(program).1.is_empty(), "{}", program);
+    }
+}
+
+#[test]
+fn more_math() {
+    for program in [
+        "x = (1 + 2)",
+        "x = (y)",
+        "x = Math.pi * 2",
+        "x = 2 * Math.pi",
+    ] {
+        assert!(reference::parse(program).is_none(), "{}", program);
+        assert!(parse(program).1.is_empty(), "{}", program);
+    }
+}
+
+#[test]
+fn bare_return_before_end() {
+    let program = "fun f() do\n  return\nend";
+
+    assert!(reference::parse(program).is_none());
+    assert!(parse(program).1.is_empty());
+}
+
+#[test]
+fn crlf_line_endings() {
+    let program = "fun f(a) do\n  # double\n  return a * 2\nend\nprint(f(2))\n";
+    let crlf = program.replace('\n', "\r\n");
+
+    let (lf, _) = parse(program);
+    let (ast, errors) = parse(&crlf);
+
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+
+    let lines = |ast: &ASTNode| -> Vec<u32> {
+        statements(ast)
+            .0
+            .iter()
+            .map(|statement| statement.span.line)
+            .collect()
+    };
+
+    assert_eq!(lines(&ast), lines(&lf));
+}
+
+#[test]
+fn block_comments() {
+    let program = "#= a\n #= nested =#\n still a comment =#\nx = 1 #= inline =# y = 2";
+    let (ast, errors) = parse(program);
+
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+    assert_eq!(statements(&ast).0.len(), 2);
+    assert_eq!(statements(&ast).0[0].span.line, 4);
+
+    let (_, errors) = parse("#= never closed\nx = 1");
+
+    assert_eq!(messages(&errors), ["unterminated block comment"]);
+}
+
+#[test]
+fn expected_tokens() {
+    let cases = [
+        ("fun f(a do end", "expected `,` or `)`, found `do`"),
+        ("if x print(1) end", "expected `do`, found `print`"),
+        ("x = [1, 2", "expected `,` or `]`, found end of file"),
+        (
+            "x = 1 +",
+            "expected a number, a name or `(`, found end of file",
+        ),
+        ("print x", "expected `=` or `(`, found `x`"),
+        ("Math.pow 2", "expected `(`, found number `2`"),
+        ("end = 1", "expected a statement, found `end`"),
+        (
+            "while x do",
+            "expected `end` to close `while` started on line 1",
+        ),
+    ];
+
+    for (program, message) in cases {
+        assert_eq!(messages(&parse(program).1), [message], "{}", program);
+    }
+}
+
+#[test]
+fn recovers_at_statement_boundaries() {
+    let program = "\
+x = 1 +
+fun f(a) do
+  y = = 2
+  if a do
+    print(a
+  end
+  return a
+end
+z = ]
+print(z)
+";
+    let (ast, errors) = parse(program);
+
+    let lines: Vec<u32> = errors
+        .iter()
+        .map(|error| error.span.unwrap().line)
+        .collect();
+
+    assert_eq!(lines, [2, 3, 6, 9]);
+
+    // The statements that parsed are kept, with what could be parsed of their bodies.
+    let block = statements(&ast);
+
+    assert_eq!(block.0.len(), 2);
+
+    let StatementKind::FunctionDefinition { body, .. } = &block.0[0].kind else {
+        panic!("expected a function, got {:?}", block.0[0].kind);
+    };
+
+    assert!(matches!(
+        body.0[..],
+        [Statement {
+            kind: StatementKind::If { .. },
+            ..
+        }]
+    ));
+    assert!(matches!(
+        &body.1,
+        ReturnStatement::Return(Expression { kind: ExpressionKind::Identifier(name), .. }) if name == "a"
+    ));
+    assert!(matches!(
+        block.0[1].kind,
+        StatementKind::FunctionCall { .. }
+    ));
+}
+
+#[test]
+fn bare_expressions_at_the_prompt() {
+    for input in ["1 + 2", "x", "Math.pi", "x > 1 and not y", "[1, {a: 2}]"] {
+        assert!(reference::parse(input).is_none(), "{}", input);
+
+        let ast = lv8_parser::parse_interactive(input, FileId::default()).unwrap();
+
+        assert!(
+            matches!(
+                statements(&ast).0[..],
+                [Statement {
+                    kind: StatementKind::Expression(_),
+                    ..
+                }]
+            ),
+            "{}",
+            input
+        );
+    }
+
+    // Calls and assignments are still statements.
+    let ast = lv8_parser::parse_interactive("f(1)\nx = y", FileId::default()).unwrap();
+
+    assert!(matches!(
+        statements(&ast).0[..],
+        [
+            Statement {
+                kind: StatementKind::FunctionCall { .. },
+                ..
+            },
+            Statement {
+                kind: StatementKind::Assignment { .. },
+                ..
+            }
+        ]
+    ));
+}
+
+#[test]
+fn incomplete_input() {
+    for input in [
+        "fun f() do",
+        "if x do\n  y = 1\nelse",
+        "x = [1,",
+        "x = \"a",
+        "f(1 +",
+    ] {
+        assert!(lv8_parser::is_incomplete(input), "{}", input);
+    }
+
+    for input in ["fun f() do end", "x = ]", "end", "1 +) 2", ""] {
+        assert!(!lv8_parser::is_incomplete(input), "{}", input);
+    }
+}
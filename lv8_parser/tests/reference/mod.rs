@@ -0,0 +1,337 @@
+//! The pest grammar the parser replaced, kept to check the hand-written parser builds the
+//! same AST for every program the grammar accepts.
+
+use std::collections::HashMap;
+
+use lv8_common::source::{FileId, LineIndex, Span};
+use lv8_parser::{
+    unescape, ASTNode, Block, ComparisonExpression, ComparisonOperation, Either, Expression,
+    ExpressionKind, LogicExpression, MathExpression, MathOperation, ReturnStatement, Statement,
+    StatementKind,
+};
+use pest::{
+    iterators::Pair,
+    pratt_parser::{Assoc, Op, PrattParser},
+    Parser,
+};
+
+#[derive(pest_derive::Parser)]
+#[grammar = "grammar/lv8.pest"]
+struct LV8Parser;
+
+struct Context<'a> {
+    input: &'a str,
+    lines: LineIndex,
+}
+
+impl Context<'_> {
+    fn span(&self, pair: &Pair<Rule>) -> Span {
+        self.span_between(pair.as_span().start(), end_of(pair))
+    }
+
+    fn span_between(&self, start: usize, end: usize) -> Span {
+        let (line, column) = self.lines.line_col(self.input, start);
+
+        Span {
+            file: FileId::default(),
+            start: start as u32,
+            end: end as u32,
+            line: line as u32,
+            column: column as u32,
+        }
+    }
+}
+
+/// Where `pair` ends. `a+` in pest skips the whitespace and comments after its first
+/// match, so `a.b` and `1 + 2` count what follows them, as do the rules ending with them.
+/// Spans end at the last token instead.
+fn end_of(pair: &Pair<Rule>) -> usize {
+    match pair.as_rule() {
+        // Neither can contain a string, every `#` starts a comment.
+        Rule::namespace | Rule::math_expr => {
+            let mut text = pair.as_str().trim_end();
+
+            while let Some(comment) = text
+                .rfind('#')
+                .filter(|&index| !text[index..].contains('\n'))
+            {
+                text = text[..comment].trim_end();
+            }
+
+            pair.as_span().start() + text.len()
+        }
+        _ => match pair.clone().into_inner().last() {
+            Some(last) if last.as_span().end() == pair.as_span().end() => end_of(&last),
+            _ => pair.as_span().end(),
+        },
+    }
+}
+
+/// The AST of `input`, `None` when the grammar rejects it.
+pub fn parse(input: &str) -> Option<ASTNode> {
+    let context = Context {
+        input,
+        lines: LineIndex::new(input),
+    };
+
+    let mut pairs = LV8Parser::parse(Rule::program, input).ok()?;
+    let block = pairs.next().unwrap().into_inner().next().unwrap();
+
+    Some(ASTNode::Block(block_of(&context, block)))
+}
+
+fn block_of(context: &Context, pair: Pair<Rule>) -> Block {
+    let end = pair.as_span().end_pos();
+    let mut statements = Vec::new();
+    let mut return_statement = ReturnStatement::Return(Expression::new(
+        ExpressionKind::Undefined,
+        context.span_between(end.pos(), end.pos()),
+    ));
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::stmt => statements.push(statement(context, pair.into_inner().next().unwrap())),
+            Rule::return_statement => {
+                let span = context.span(&pair);
+
+                return_statement = match pair.as_str() {
+                    "break" => ReturnStatement::Break,
+                    "continue" => ReturnStatement::Continue,
+                    _ => match pair.into_inner().next() {
+                        Some(pair) => ReturnStatement::Return(expression(context, pair)),
+                        None => ReturnStatement::Return(Expression::new(
+                            ExpressionKind::Undefined,
+                            span,
+                        )),
+                    },
+                }
+            }
+            rule => unreachable!("{:?}", rule),
+        }
+    }
+
+    Block(statements, return_statement)
+}
+
+fn statement_or_expression(context: &Context, pair: Pair<Rule>) -> Either<Expression, Statement> {
+    match pair.as_rule() {
+        Rule::expr => Either::Left(expression(context, pair)),
+        _ => Either::Right(statement(context, pair.into_inner().next().unwrap())),
+    }
+}
+
+fn statement(context: &Context, pair: Pair<Rule>) -> Statement {
+    let span = context.span(&pair);
+    let rule = pair.as_rule();
+    let mut inner = pair.into_inner();
+
+    let kind = match rule {
+        Rule::assign => StatementKind::Assignment {
+            left: names(inner.next().unwrap()),
+            right: match statement_or_expression(context, inner.next().unwrap()) {
+                Either::Left(expression) => Either::Left(expression),
+                Either::Right(statement) => Either::Right(Box::new(statement)),
+            },
+        },
+        Rule::function_call => StatementKind::FunctionCall {
+            expression: expression(context, inner.next().unwrap()),
+            arguments: inner
+                .map(|pair| statement_or_expression(context, pair))
+                .collect(),
+        },
+        Rule::function_def => {
+            let name = inner.next().unwrap().as_str().to_string();
+            let mut parameters = Vec::new();
+            let mut body = inner.next().unwrap();
+
+            if body.as_rule() == Rule::ident_list {
+                parameters = names(body);
+                body = inner.next().unwrap();
+            }
+
+            StatementKind::FunctionDefinition {
+                name,
+                parameters,
+                body: block_of(context, body),
+            }
+        }
+        Rule::if_statement => {
+            let condition = expression(context, inner.next().unwrap());
+            let body = block_of(context, inner.next().unwrap());
+            let mut else_if = Vec::new();
+            let mut else_body = None;
+
+            while let Some(pair) = inner.next() {
+                match pair.as_rule() {
+                    Rule::logic_expr => else_if.push((
+                        expression(context, pair),
+                        block_of(context, inner.next().unwrap()),
+                    )),
+                    _ => else_body = Some(block_of(context, pair)),
+                }
+            }
+
+            StatementKind::If {
+                condition,
+                body,
+                else_if,
+                else_body,
+            }
+        }
+        Rule::while_statement => StatementKind::While {
+            condition: expression(context, inner.next().unwrap()),
+            body: block_of(context, inner.next().unwrap()),
+        },
+        Rule::module_def => StatementKind::ModuleDefinition {
+            name: inner.next().unwrap().as_str().to_string(),
+            body: block_of(context, inner.next().unwrap()),
+        },
+        Rule::import_statement => StatementKind::Import {
+            path: unescape(inner.next().unwrap().into_inner().next().unwrap().as_str()),
+            ident: inner.next().unwrap().as_str().to_string(),
+        },
+        rule => unreachable!("{:?}", rule),
+    };
+
+    Statement { kind, span }
+}
+
+fn names(pair: Pair<Rule>) -> Vec<String> {
+    pair.into_inner()
+        .map(|pair| pair.as_str().to_string())
+        .collect()
+}
+
+fn expression(context: &Context, pair: Pair<Rule>) -> Expression {
+    let span = context.span(&pair);
+
+    let kind = match pair.as_rule() {
+        Rule::expr => return expression(context, pair.into_inner().next().unwrap()),
+        Rule::math_expr => return math(context, pair),
+        Rule::logic_expr => return logic(context, pair),
+        Rule::null => ExpressionKind::Null,
+        Rule::undefined => ExpressionKind::Undefined,
+        Rule::boolean => ExpressionKind::Boolean(pair.as_str() == "true"),
+        Rule::number => {
+            let number = pair.into_inner().next().unwrap();
+
+            ExpressionKind::Number(match number.as_rule() {
+                Rule::integer => Either::Left(number.as_str().parse().unwrap()),
+                _ => Either::Right(number.as_str().parse().unwrap()),
+            })
+        }
+        Rule::string => ExpressionKind::String(unescape(pair.into_inner().as_str())),
+        Rule::ident => ExpressionKind::Identifier(pair.as_str().to_string()),
+        Rule::namespace => ExpressionKind::Namespace(names(pair)),
+        Rule::array => ExpressionKind::Array(
+            pair.into_inner()
+                .map(|pair| expression(context, pair))
+                .collect(),
+        ),
+        Rule::object => {
+            let mut object = HashMap::new();
+
+            for pair in pair.into_inner() {
+                let mut inner = pair.into_inner();
+                let key = inner.next().unwrap();
+                let key = match key.as_rule() {
+                    Rule::string => unescape(key.into_inner().as_str()),
+                    _ => key.as_str().to_string(),
+                };
+
+                object.insert(key, expression(context, inner.next().unwrap()));
+            }
+
+            ExpressionKind::Object(object)
+        }
+        rule => unreachable!("{:?}", rule),
+    };
+
+    Expression::new(kind, span)
+}
+
+fn math(context: &Context, pair: Pair<Rule>) -> Expression {
+    PrattParser::new()
+        .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::subtract, Assoc::Left))
+        .op(Op::infix(Rule::multiply, Assoc::Left)
+            | Op::infix(Rule::divide, Assoc::Left)
+            | Op::infix(Rule::modulus, Assoc::Left))
+        .op(Op::infix(Rule::exponentiation, Assoc::Right))
+        .map_primary(|pair| expression(context, pair))
+        .map_infix(|left, op, right| {
+            let operation = match op.as_rule() {
+                Rule::add => MathOperation::Add,
+                Rule::subtract => MathOperation::Subtract,
+                Rule::multiply => MathOperation::Multiply,
+                Rule::divide => MathOperation::Divide,
+                Rule::modulus => MathOperation::Modulus,
+                _ => MathOperation::Exponentiation,
+            };
+            let span = left.span.to(&right.span);
+
+            Expression::new(
+                ExpressionKind::MathExpression(MathExpression::Operation {
+                    left: Box::new(left),
+                    operation,
+                    right: Box::new(right),
+                }),
+                span,
+            )
+        })
+        .parse(pair.into_inner())
+}
+
+fn logic(context: &Context, pair: Pair<Rule>) -> Expression {
+    let comparisons = [
+        Rule::greather,
+        Rule::greather_eq,
+        Rule::less,
+        Rule::less_eq,
+        Rule::equal,
+        Rule::not_equal,
+    ]
+    .into_iter()
+    .map(|rule| Op::infix(rule, Assoc::Left))
+    .reduce(|left, right| left | right)
+    .unwrap();
+
+    PrattParser::new()
+        .op(Op::infix(Rule::and, Assoc::Left) | Op::infix(Rule::or, Assoc::Left))
+        .op(Op::prefix(Rule::not))
+        .op(comparisons)
+        .map_primary(|pair| expression(context, pair))
+        .map_prefix(|op, expr| {
+            let span = context.span(&op).to(&expr.span);
+
+            Expression::new(
+                ExpressionKind::LogicExpression(LogicExpression::Not {
+                    expr: Box::new(expr),
+                }),
+                span,
+            )
+        })
+        .map_infix(|left, op, right| {
+            let span = left.span.to(&right.span);
+            let (left, right) = (Box::new(left), Box::new(right));
+
+            let kind = match op.as_rule() {
+                Rule::and => ExpressionKind::LogicExpression(LogicExpression::And { left, right }),
+                Rule::or => ExpressionKind::LogicExpression(LogicExpression::Or { left, right }),
+                rule => ExpressionKind::ComparisonExpression(ComparisonExpression {
+                    left,
+                    operation: match rule {
+                        Rule::greather => ComparisonOperation::GreaterThan,
+                        Rule::greather_eq => ComparisonOperation::GreaterThanOrEqual,
+                        Rule::less => ComparisonOperation::LessThan,
+                        Rule::less_eq => ComparisonOperation::LessThanOrEqual,
+                        Rule::equal => ComparisonOperation::Equal,
+                        _ => ComparisonOperation::NotEqual,
+                    },
+                    right,
+                }),
+            };
+
+            Expression::new(kind, span)
+        })
+        .parse(pair.into_inner())
+}